@@ -0,0 +1,816 @@
+/*
+binary search tree with all nodes in one contiguous vector (arena), linked with indexes.
+STRUCTURE:
+    * nodes: Vec of slots, a slot is either an occupied node or a free slot
+    * a node stores its data and the indexes of its parent, left child and right child
+      (u32 instead of usize, it halves the size of the links)
+    * free slots are chained into a free list (free_head -> next free -> ...). Insertion reuses
+      the last freed slot before growing the vector, so deleting does not leak slots.
+PROPERTIES:
+    - same rules and same operations as BinarySearchTree (no duplicates, left < node < right)
+WHY:
+    - every BinarySearchTree node is a separate heap allocation holding two reference counts,
+      a RefCell borrow flag and Rc/Weak pointers. Here a node is only its data plus three
+      indexes, nodes are next to each other in memory and no borrow flags are checked.
+    - links are plain indexes, there is no Weak::upgrade or borrow() on every step of a walk
+CONS:
+    - a search returns an index, not a node handle. The index is only valid until that node is
+      deleted, after that the slot may be reused by another key.
+*/
+
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{GroupedTraversalType, TraversalType};
+
+type NodeIndex = u32;
+
+#[derive(Debug)]
+pub struct ArenaBinarySearchTree<K> {
+    nodes: Vec<Slot<K>>,
+    root: Option<NodeIndex>,
+    free_head: Option<NodeIndex>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct ArenaNode<K> {
+    data: K,
+    parent: Option<NodeIndex>,
+    left_child: Option<NodeIndex>,
+    right_child: Option<NodeIndex>,
+}
+
+#[derive(Debug)]
+enum Slot<K> {
+    Occupied(ArenaNode<K>),
+    // free slot, points to the next free slot of the free list
+    Free(Option<NodeIndex>),
+}
+
+pub struct ArenaIter<'a, K> {
+    tree: &'a ArenaBinarySearchTree<K>,
+    front: Option<NodeIndex>,
+    back: Option<NodeIndex>,
+    remaining: usize,
+}
+
+impl<K> Default for ArenaBinarySearchTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> ArenaBinarySearchTree<K> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: None,
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    // index of the root node, usable with key_at
+    pub fn get_root(&self) -> Option<usize> {
+        self.root.map(|root| root as usize)
+    }
+
+    // key of the node at index, None for a free or out of bounds slot
+    pub fn key_at(
+        &self,
+        index: usize,
+    ) -> Option<&K> {
+        match self.nodes.get(index) {
+            Some(Slot::Occupied(node)) => Some(&node.data),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> ArenaIter<'_, K> {
+        ArenaIter {
+            tree: self,
+            front: self.root.map(|root| self.min_of_subtree(root)),
+            back: self.root.map(|root| self.max_of_subtree(root)),
+            remaining: self.len,
+        }
+    }
+
+    fn node(
+        &self,
+        index: NodeIndex,
+    ) -> &ArenaNode<K> {
+        match &self.nodes[index as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Free(_) => unreachable!("link to a free slot {}", index),
+        }
+    }
+
+    fn node_mut(
+        &mut self,
+        index: NodeIndex,
+    ) -> &mut ArenaNode<K> {
+        match &mut self.nodes[index as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Free(_) => unreachable!("link to a free slot {}", index),
+        }
+    }
+
+    // puts the node into the last freed slot, or at the end of the vector
+    fn allocate(
+        &mut self,
+        node: ArenaNode<K>,
+    ) -> NodeIndex {
+        match self.free_head {
+            Some(free_index) => {
+                let slot = &mut self.nodes[free_index as usize];
+                self.free_head = match slot {
+                    Slot::Free(next_free) => *next_free,
+                    Slot::Occupied(_) => {
+                        unreachable!("free list points to a node")
+                    }
+                };
+                *slot = Slot::Occupied(node);
+                free_index
+            }
+            None => {
+                let index = NodeIndex::try_from(self.nodes.len())
+                    .expect("arena is full, indexes are u32");
+                self.nodes.push(Slot::Occupied(node));
+                index
+            }
+        }
+    }
+
+    // takes the node out of its slot and puts the slot on the free list
+    fn release(
+        &mut self,
+        index: NodeIndex,
+    ) -> ArenaNode<K> {
+        let slot = std::mem::replace(
+            &mut self.nodes[index as usize],
+            Slot::Free(self.free_head),
+        );
+        self.free_head = Some(index);
+
+        match slot {
+            Slot::Occupied(node) => node,
+            Slot::Free(_) => {
+                unreachable!("releasing a free slot {}", index)
+            }
+        }
+    }
+
+    fn min_of_subtree(
+        &self,
+        mut index: NodeIndex,
+    ) -> NodeIndex {
+        while let Some(left) = self.node(index).left_child {
+            index = left;
+        }
+        index
+    }
+
+    fn max_of_subtree(
+        &self,
+        mut index: NodeIndex,
+    ) -> NodeIndex {
+        while let Some(right) = self.node(index).right_child {
+            index = right;
+        }
+        index
+    }
+
+    fn next_in_order(
+        &self,
+        index: NodeIndex,
+    ) -> Option<NodeIndex> {
+        if let Some(right) = self.node(index).right_child {
+            return Some(self.min_of_subtree(right));
+        }
+
+        let mut child = index;
+        let mut maybe_parent = self.node(index).parent;
+        while let Some(parent) = maybe_parent {
+            if self.node(parent).left_child == Some(child) {
+                return Some(parent);
+            }
+            child = parent;
+            maybe_parent = self.node(parent).parent;
+        }
+
+        None
+    }
+
+    fn prev_in_order(
+        &self,
+        index: NodeIndex,
+    ) -> Option<NodeIndex> {
+        if let Some(left) = self.node(index).left_child {
+            return Some(self.max_of_subtree(left));
+        }
+
+        let mut child = index;
+        let mut maybe_parent = self.node(index).parent;
+        while let Some(parent) = maybe_parent {
+            if self.node(parent).right_child == Some(child) {
+                return Some(parent);
+            }
+            child = parent;
+            maybe_parent = self.node(parent).parent;
+        }
+
+        None
+    }
+
+    // points parent's link (or the root) that pointed to old_child to new_child
+    fn replace_child(
+        &mut self,
+        parent: Option<NodeIndex>,
+        old_child: NodeIndex,
+        new_child: Option<NodeIndex>,
+    ) {
+        match parent {
+            Some(parent) => {
+                let parent_node = self.node_mut(parent);
+                if parent_node.left_child == Some(old_child) {
+                    parent_node.left_child = new_child;
+                } else {
+                    parent_node.right_child = new_child;
+                }
+            }
+            None => self.root = new_child,
+        }
+
+        if let Some(new_child) = new_child {
+            self.node_mut(new_child).parent = parent;
+        }
+    }
+
+    // same 3 cases as BinarySearchTree::delete_node, returns the removed key
+    fn delete_node(
+        &mut self,
+        target: NodeIndex,
+    ) -> K {
+        let (parent, left_child, right_child) = {
+            let target_node = self.node(target);
+            (
+                target_node.parent,
+                target_node.left_child,
+                target_node.right_child,
+            )
+        };
+
+        match (left_child, right_child) {
+            (Some(left), Some(_)) => {
+                // 2 childs: swap the data with max_lsub, then remove max_lsub
+                // (it has no right child, so it is case 0 or 1)
+                let max_lsub = self.max_of_subtree(left);
+                self.swap_data(target, max_lsub);
+                self.delete_node(max_lsub)
+            }
+            (maybe_child, None) | (None, maybe_child) => {
+                self.replace_child(parent, target, maybe_child);
+                self.len -= 1;
+                self.release(target).data
+            }
+        }
+    }
+
+    fn swap_data(
+        &mut self,
+        first: NodeIndex,
+        second: NodeIndex,
+    ) {
+        let (low, high) = if first < second {
+            (first as usize, second as usize)
+        } else {
+            (second as usize, first as usize)
+        };
+
+        let (head, tail) = self.nodes.split_at_mut(high);
+        match (&mut head[low], &mut tail[0]) {
+            (Slot::Occupied(low_node), Slot::Occupied(high_node)) => {
+                std::mem::swap(&mut low_node.data, &mut high_node.data);
+            }
+            _ => unreachable!("swapping data of a free slot"),
+        }
+    }
+}
+
+impl<K: Ord> ArenaBinarySearchTree<K> {
+    pub fn insert(
+        &mut self,
+        insert_val: K,
+    ) {
+        let Some(mut current) = self.root else {
+            // no node in the tree, this node will be the root node
+            let root = self.allocate(ArenaNode {
+                data: insert_val,
+                parent: None,
+                left_child: None,
+                right_child: None,
+            });
+            self.root = Some(root);
+            self.len += 1;
+            return;
+        };
+
+        loop {
+            let current_node = self.node(current);
+            let next = if insert_val == current_node.data {
+                // node already exists in the tree, no need to make an insertion
+                return;
+            } else if insert_val < current_node.data {
+                current_node.left_child
+            } else {
+                current_node.right_child
+            };
+
+            match next {
+                Some(next) => current = next,
+                None => {
+                    let goes_left = insert_val < current_node.data;
+                    let new_index = self.allocate(ArenaNode {
+                        data: insert_val,
+                        parent: Some(current),
+                        left_child: None,
+                        right_child: None,
+                    });
+
+                    let parent_node = self.node_mut(current);
+                    if goes_left {
+                        parent_node.left_child = Some(new_index);
+                    } else {
+                        parent_node.right_child = Some(new_index);
+                    }
+
+                    self.len += 1;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn delete(
+        &mut self,
+        key: &K,
+    ) {
+        if let Some(target) = self.search_index(key) {
+            self.delete_node(target);
+        }
+    }
+
+    // index of the node holding key, usable with key_at
+    pub fn search(
+        &self,
+        key: &K,
+    ) -> Option<usize> {
+        self.search_index(key).map(|index| index as usize)
+    }
+
+    fn search_index(
+        &self,
+        key: &K,
+    ) -> Option<NodeIndex> {
+        let mut current_node_ptr = self.root;
+
+        while let Some(current) = current_node_ptr {
+            let current_node = self.node(current);
+
+            if *key == current_node.data {
+                return Some(current);
+            } else if *key < current_node.data {
+                current_node_ptr = current_node.left_child;
+            } else {
+                current_node_ptr = current_node.right_child;
+            }
+        }
+
+        None
+    }
+}
+
+impl<K: Clone> ArenaBinarySearchTree<K> {
+    // same orders and same results as BinarySearchTree::traverse
+    pub fn traverse(
+        &self,
+        traversal_type: TraversalType,
+    ) -> Option<Vec<K>> {
+        let root = self.root?;
+
+        let keys = match traversal_type {
+            TraversalType::InOrder => self.iter().cloned().collect(),
+            TraversalType::ReverseInOrder => {
+                self.iter().rev().cloned().collect()
+            }
+            TraversalType::PreOrder => {
+                let mut keys = Vec::with_capacity(self.len);
+                let mut stack = vec![root];
+
+                while let Some(index) = stack.pop() {
+                    let node = self.node(index);
+                    keys.push(node.data.clone());
+                    stack.extend(node.right_child);
+                    stack.extend(node.left_child);
+                }
+                keys
+            }
+            TraversalType::PostOrder => {
+                // reversed (node, right, left) pre-order is (left, right, node)
+                let mut keys = Vec::with_capacity(self.len);
+                let mut stack = vec![root];
+
+                while let Some(index) = stack.pop() {
+                    let node = self.node(index);
+                    keys.push(node.data.clone());
+                    stack.extend(node.left_child);
+                    stack.extend(node.right_child);
+                }
+                keys.reverse();
+                keys
+            }
+            TraversalType::LevelOrder => self.levels(root).concat(),
+            TraversalType::RightSideView => self
+                .levels(root)
+                .into_iter()
+                .filter_map(|mut level| level.pop())
+                .collect(),
+        };
+
+        Some(keys)
+    }
+
+    pub fn traverse_grouped(
+        &self,
+        traversal_type: GroupedTraversalType,
+    ) -> Option<Vec<Vec<K>>>
+    where
+        K: Ord,
+    {
+        let root = self.root?;
+
+        let groups = match traversal_type {
+            GroupedTraversalType::Levels => self.levels(root),
+            GroupedTraversalType::ZigZag => {
+                let mut levels = self.levels(root);
+                for level in levels.iter_mut().skip(1).step_by(2) {
+                    level.reverse();
+                }
+                levels
+            }
+            GroupedTraversalType::Vertical => {
+                let mut columns: BTreeMap<i64, Vec<(usize, K)>> =
+                    BTreeMap::new();
+                let mut queue = VecDeque::from([(root, 0i64, 0usize)]);
+
+                while let Some((index, column, depth)) = queue.pop_front() {
+                    let node = self.node(index);
+                    columns
+                        .entry(column)
+                        .or_default()
+                        .push((depth, node.data.clone()));
+                    queue.extend(
+                        node.left_child
+                            .map(|left| (left, column - 1, depth + 1)),
+                    );
+                    queue.extend(
+                        node.right_child
+                            .map(|right| (right, column + 1, depth + 1)),
+                    );
+                }
+
+                columns
+                    .into_values()
+                    .map(|mut entries| {
+                        entries.sort();
+                        entries.into_iter().map(|(_, key)| key).collect()
+                    })
+                    .collect()
+            }
+        };
+
+        Some(groups)
+    }
+
+    fn levels(
+        &self,
+        root: NodeIndex,
+    ) -> Vec<Vec<K>> {
+        let mut levels = Vec::new();
+        let mut current_level = vec![root];
+
+        while !current_level.is_empty() {
+            let mut next_level = Vec::new();
+            let mut keys = Vec::with_capacity(current_level.len());
+
+            for &index in &current_level {
+                let node = self.node(index);
+                keys.push(node.data.clone());
+                next_level.extend(node.left_child);
+                next_level.extend(node.right_child);
+            }
+
+            levels.push(keys);
+            current_level = next_level;
+        }
+
+        levels
+    }
+}
+
+// nodes are stored by value in the vector, references to the keys can be handed out directly
+impl<'a, K> Iterator for ArenaIter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let front = self.front.take()?;
+        self.remaining -= 1;
+        self.front = self.tree.next_in_order(front);
+
+        Some(&self.tree.node(front).data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K> DoubleEndedIterator for ArenaIter<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let back = self.back.take()?;
+        self.remaining -= 1;
+        self.back = self.tree.prev_in_order(back);
+
+        Some(&self.tree.node(back).data)
+    }
+}
+
+impl<K> ExactSizeIterator for ArenaIter<'_, K> {}
+
+impl<'a, K> IntoIterator for &'a ArenaBinarySearchTree<K> {
+    type Item = &'a K;
+    type IntoIter = ArenaIter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K> IntoIterator for ArenaBinarySearchTree<K> {
+    type Item = K;
+    type IntoIter = std::vec::IntoIter<K>;
+
+    // nodes have no pointers between each other, so the tree is taken apart slot by slot
+    // in sorted order
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut order = Vec::with_capacity(self.len);
+        let mut maybe_index =
+            self.root.map(|root| self.min_of_subtree(root));
+
+        while let Some(index) = maybe_index {
+            order.push(index);
+            maybe_index = self.next_in_order(index);
+        }
+
+        order
+            .into_iter()
+            .map(|index| self.release(index).data)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod arena_tests {
+    use std::{
+        cell::RefCell,
+        collections::BTreeSet,
+        hint::black_box,
+        mem::size_of,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::dsa::tree::bs_tree::{
+        BinarySearchTree, BinarySearchTreeNode,
+    };
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum ArenaOp {
+        Insert(i16),
+        Delete(i16),
+    }
+
+    fn arena_op_strategy() -> impl Strategy<Value = ArenaOp> {
+        prop_oneof![
+            (-100i16..100).prop_map(ArenaOp::Insert),
+            (-100i16..100).prop_map(ArenaOp::Delete),
+        ]
+    }
+
+    #[test]
+    fn matches_rc_tree_after_random_operations() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy =
+            proptest::collection::vec(arena_op_strategy(), 1..500);
+
+        runner
+            .run(&strategy, |ops| {
+                let mut arena_tree = ArenaBinarySearchTree::new();
+                let mut rc_tree = BinarySearchTree::new();
+                let mut expected = BTreeSet::new();
+
+                for op in ops {
+                    match op {
+                        ArenaOp::Insert(key) => {
+                            arena_tree.insert(key);
+                            rc_tree.insert(key);
+                            expected.insert(key);
+                        }
+                        ArenaOp::Delete(key) => {
+                            arena_tree.delete(&key);
+                            rc_tree.delete(&key);
+                            expected.remove(&key);
+                        }
+                    }
+                }
+
+                prop_assert_eq!(arena_tree.get_len(), expected.len());
+                // freed slots are reused, the arena never has more slots than keys ever alive
+                prop_assert!(arena_tree.nodes.len() <= 200);
+
+                for key in -100i16..100 {
+                    let found = arena_tree.search(&key);
+                    prop_assert_eq!(
+                        found.is_some(),
+                        expected.contains(&key)
+                    );
+                    if let Some(index) = found {
+                        prop_assert_eq!(
+                            arena_tree.key_at(index),
+                            Some(&key)
+                        );
+                    }
+                }
+
+                let flat_orders = [
+                    TraversalType::InOrder,
+                    TraversalType::PreOrder,
+                    TraversalType::PostOrder,
+                    TraversalType::ReverseInOrder,
+                    TraversalType::LevelOrder,
+                    TraversalType::RightSideView,
+                ];
+                for traversal_type in flat_orders {
+                    let arena_keys = arena_tree.traverse(traversal_type);
+                    prop_assert_eq!(
+                        arena_keys,
+                        rc_tree.traverse(traversal_type)
+                    );
+                }
+
+                let grouped_orders = [
+                    GroupedTraversalType::Levels,
+                    GroupedTraversalType::ZigZag,
+                    GroupedTraversalType::Vertical,
+                ];
+                for traversal_type in grouped_orders {
+                    prop_assert_eq!(
+                        arena_tree.traverse_grouped(traversal_type),
+                        rc_tree.traverse_grouped(traversal_type)
+                    );
+                }
+
+                prop_assert_eq!(
+                    arena_tree.iter().rev().copied().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>()
+                );
+                prop_assert_eq!(
+                    arena_tree.into_iter().collect::<Vec<_>>(),
+                    expected.into_iter().collect::<Vec<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    // random but repeatable keys (xorshift), sorted input would make both trees degenerate
+    fn bench_keys(count: usize) -> Vec<u64> {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+            .collect()
+    }
+
+    fn ns_per_op(
+        elapsed: Duration,
+        ops: usize,
+    ) -> f64 {
+        elapsed.as_nanos() as f64 / ops as f64
+    }
+
+    // run with `just bench_bst` (release mode):
+    // cargo test --release bench_arena_against_rc_tree -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_arena_against_rc_tree() {
+        const COUNT: usize = 1_000_000;
+        let keys = bench_keys(COUNT);
+
+        let start = Instant::now();
+        let mut rc_tree = BinarySearchTree::new();
+        for &key in &keys {
+            rc_tree.insert(key);
+        }
+        let rc_insert = start.elapsed();
+
+        let start = Instant::now();
+        for key in &keys {
+            black_box(rc_tree.search(key));
+        }
+        let rc_search = start.elapsed();
+
+        let start = Instant::now();
+        for key in &keys {
+            rc_tree.delete(key);
+        }
+        let rc_delete = start.elapsed();
+
+        let start = Instant::now();
+        let mut arena_tree = ArenaBinarySearchTree::new();
+        for &key in &keys {
+            arena_tree.insert(key);
+        }
+        let arena_insert = start.elapsed();
+
+        let start = Instant::now();
+        for key in &keys {
+            black_box(arena_tree.search(key));
+        }
+        let arena_search = start.elapsed();
+
+        let start = Instant::now();
+        for key in &keys {
+            arena_tree.delete(key);
+        }
+        let arena_delete = start.elapsed();
+
+        // Rc allocation: strong + weak counts, then the RefCell (borrow flag + node).
+        // the allocator adds its own bookkeeping on top of that for every node.
+        let rc_node_bytes = 2 * size_of::<usize>()
+            + size_of::<RefCell<BinarySearchTreeNode<u64>>>();
+        // one slot per node, no extra allocation (vector growth aside)
+        let arena_node_bytes = size_of::<Slot<u64>>();
+
+        println!("{} random u64 keys", COUNT);
+        println!(
+            "{:<8} {:>12} {:>12} {:>12} {:>14}",
+            "tree", "insert ns", "search ns", "delete ns", "bytes/node"
+        );
+        println!(
+            "{:<8} {:>12.1} {:>12.1} {:>12.1} {:>14}",
+            "rc",
+            ns_per_op(rc_insert, COUNT),
+            ns_per_op(rc_search, COUNT),
+            ns_per_op(rc_delete, COUNT),
+            format!("{} + malloc", rc_node_bytes)
+        );
+        println!(
+            "{:<8} {:>12.1} {:>12.1} {:>12.1} {:>14}",
+            "arena",
+            ns_per_op(arena_insert, COUNT),
+            ns_per_op(arena_search, COUNT),
+            ns_per_op(arena_delete, COUNT),
+            arena_node_bytes
+        );
+
+        assert_eq!(rc_tree.get_len(), 0);
+        assert_eq!(arena_tree.get_len(), 0);
+    }
+}
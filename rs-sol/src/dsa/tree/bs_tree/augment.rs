@@ -0,0 +1,239 @@
+/*
+subtree augmentation: every node keeps a summary of all keys in its subtree (sum, min, max,
+count of something, ...). The summary type is the second type parameter of the tree,
+BinarySearchTree<K, A>, the plain tree uses () which keeps nothing.
+    * a summary is built from single keys (from_key) and glued together (combine). combine must
+      be associative, it does not have to be commutative: the keys of the left subtree are
+      always combined before the node's key and the right subtree after it, so the result is
+      in key order (a monoid without the identity element).
+    * insert and delete_node recompute the summaries on the path from the linked/unlinked node
+      up to the root (update_upward), like the subtree sizes. O(height) per operation.
+    * fold_range(a..b): summary of the keys in the range. The search paths of both bounds
+      are the same until the first node inside the range (split node). Below it, on the left
+      only the lower bound matters: every node that satisfies it is taken with its whole right
+      subtree (a stored summary), then we go left. The right side is the mirror. So only
+      O(height) summaries are combined, no matter how many keys are in the range.
+*/
+
+use std::ops::RangeBounds;
+
+use super::{BinarySearchTree, BinarySearchTreeNode, Comparator};
+
+pub trait Augmentation<K>: Sized {
+    // summary of a subtree that has only this key
+    fn from_key(key: &K) -> Self;
+
+    // summary of self's keys followed by other's keys
+    fn combine(
+        &self,
+        other: &Self,
+    ) -> Self;
+}
+
+// no augmentation, nothing to keep
+impl<K> Augmentation<K> for () {
+    fn from_key(_key: &K) -> Self {}
+
+    fn combine(
+        &self,
+        _other: &Self,
+    ) -> Self {
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // summary of the keys in the range, None if there is no key in the range
+    pub fn fold_range(
+        &self,
+        bounds: impl RangeBounds<K>,
+    ) -> Option<A> {
+        let (lower, upper) = (bounds.start_bound(), bounds.end_bound());
+
+        let mut current_node_ptr = self.root.clone();
+        let split_rc = loop {
+            let node_rc = current_node_ptr?;
+            let node = node_rc.borrow();
+
+            current_node_ptr = if !self.satisfies_lower(&node.data, lower) {
+                node.right_child.clone()
+            } else if !self.satisfies_upper(&node.data, upper) {
+                node.left_child.clone()
+            } else {
+                drop(node);
+                break node_rc;
+            };
+        };
+
+        let split = split_rc.borrow();
+        let mut summary = A::from_key(&split.data);
+
+        // every node found here is smaller than the ones before it, so its part goes in front
+        let mut current_node_ptr = split.left_child.clone();
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            current_node_ptr = if self.satisfies_lower(&node.data, lower) {
+                summary = Self::key_and_right(&node).combine(&summary);
+                node.left_child.clone()
+            } else {
+                node.right_child.clone()
+            };
+        }
+
+        let mut current_node_ptr = split.right_child.clone();
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            current_node_ptr = if self.satisfies_upper(&node.data, upper) {
+                summary = summary.combine(&Self::left_and_key(&node));
+                node.right_child.clone()
+            } else {
+                node.left_child.clone()
+            };
+        }
+
+        Some(summary)
+    }
+
+    // the node's key followed by its right subtree
+    fn key_and_right(node: &BinarySearchTreeNode<K, A>) -> A {
+        let summary = A::from_key(&node.data);
+
+        match node.right_child.as_ref() {
+            Some(right_rc) => summary.combine(&right_rc.borrow().summary),
+            None => summary,
+        }
+    }
+
+    // the node's left subtree followed by its key
+    fn left_and_key(node: &BinarySearchTreeNode<K, A>) -> A {
+        let summary = A::from_key(&node.data);
+
+        match node.left_child.as_ref() {
+            Some(left_rc) => left_rc.borrow().summary.combine(&summary),
+            None => summary,
+        }
+    }
+}
+
+#[cfg(test)]
+mod augment_tests {
+    use std::{collections::BTreeSet, ops::Bound};
+
+    use super::super::test_utils::Sum;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Max(i32);
+
+    impl Augmentation<i32> for Max {
+        fn from_key(key: &i32) -> Self {
+            Max(*key)
+        }
+
+        fn combine(
+            &self,
+            other: &Self,
+        ) -> Self {
+            Max(self.0.max(other.0))
+        }
+    }
+
+    // not commutative, any key out of order shows up in the result
+    #[derive(Debug, PartialEq)]
+    struct KeyList(Vec<i32>);
+
+    impl Augmentation<i32> for KeyList {
+        fn from_key(key: &i32) -> Self {
+            KeyList(vec![*key])
+        }
+
+        fn combine(
+            &self,
+            other: &Self,
+        ) -> Self {
+            KeyList([self.0.as_slice(), other.0.as_slice()].concat())
+        }
+    }
+
+    fn bound_strategy() -> impl Strategy<Value = Bound<i32>> {
+        prop_oneof![
+            (-60i32..60).prop_map(Bound::Included),
+            (-60i32..60).prop_map(Bound::Excluded),
+            Just(Bound::Unbounded),
+        ]
+    }
+
+    #[test]
+    fn fold_range_keeps_key_order_after_inserts_and_deletes() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec((any::<bool>(), -50i32..50), 0..200),
+            bound_strategy(),
+            bound_strategy(),
+        );
+
+        runner
+            .run(&strategy, |(operations, lower, upper)| {
+                let mut bstree: BinarySearchTree<i32, KeyList> =
+                    BinarySearchTree::with_augmentation();
+                let mut expected = BTreeSet::new();
+
+                for (is_insert, value) in operations {
+                    if is_insert {
+                        bstree.insert(value);
+                        expected.insert(value);
+                    } else {
+                        bstree.delete(&value);
+                        expected.remove(&value);
+                    }
+                }
+
+                let in_range: Vec<i32> = expected
+                    .iter()
+                    .copied()
+                    .filter(|key| (lower, upper).contains(key))
+                    .collect();
+                let folded =
+                    bstree.fold_range((lower, upper)).map(|list| list.0);
+
+                prop_assert_eq!(folded.unwrap_or_default(), in_range);
+                prop_assert_eq!(
+                    bstree
+                        .fold_range(..)
+                        .map(|list| list.0)
+                        .unwrap_or_default(),
+                    expected.into_iter().collect::<Vec<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn sum_and_max_of_ranges() {
+        let mut sums: BinarySearchTree<i32, Sum> =
+            BinarySearchTree::with_augmentation();
+        let mut maxes: BinarySearchTree<i32, Max> =
+            BinarySearchTree::default();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            sums.insert(value);
+            maxes.insert(value);
+        }
+
+        assert_eq!(sums.fold_range(..), Some(Sum(350)));
+        assert_eq!(sums.fold_range(30..=60), Some(Sum(180)));
+        assert_eq!(sums.fold_range(41..50), None);
+        assert_eq!(maxes.fold_range(..65), Some(Max(60)));
+
+        // two-child delete of the root, the summaries on the swapped path must follow
+        sums.delete(&50);
+        maxes.delete(&80);
+        assert_eq!(sums.fold_range(30..=60), Some(Sum(130)));
+        assert_eq!(maxes.fold_range(..), Some(Max(70)));
+        assert_eq!(
+            sums.iter().collect::<Vec<_>>(),
+            vec![20, 30, 40, 60, 70, 80]
+        );
+    }
+}
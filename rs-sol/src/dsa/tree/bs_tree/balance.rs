@@ -0,0 +1,334 @@
+/*
+balanced trees: the header of mod.rs says the insertion order decides the height. These
+methods build a balanced tree directly, or fix the shape of an existing one.
+    * from_sorted: the middle key of a sorted sequence is the root, the keys before it build
+      the left subtree and the keys after it the right one. Building the subtrees in key order
+      (left subtree, node, right subtree) consumes the keys one by one from the iterator, so
+      every key is touched once, O(n). Sizes of the two subtrees differ by at most one, so the
+      height is the minimum possible (ceil(log2(n + 1))). The recursion is only log n deep.
+    * FromIterator: any order and duplicates are allowed, the keys are sorted and deduped first
+      (by the comparator of the tree, see comparator.rs).
+    * rebalance: Day-Stout-Warren, in place, O(n) time and O(1) extra memory.
+        1. tree to vine: rotate right until there is no left child anywhere. The tree becomes
+           a sorted linked list going right (vine).
+        2. vine to tree: left rotations on every second node of the vine halve it (compress).
+           First the keys that do not fit in a perfect tree are moved down to the last
+           level, then compress is repeated with m / 2 rotations until the vine is gone.
+      rotations only change the links of two nodes and a moved subtree, parent pointers and
+      size/summary of the two nodes are fixed right there.
+*/
+
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+impl<K: Ord> BinarySearchTree<K> {
+    // keys must be in increasing order, equal neighbours are kept once
+    pub fn from_sorted(keys: impl IntoIterator<Item = K>) -> Self {
+        let mut bstree = Self::new();
+        bstree.fill_from_sorted(keys);
+
+        bstree
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K> + Default> FromIterator<K>
+    for BinarySearchTree<K, A, C>
+{
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut bstree = Self::default();
+        let mut keys: Vec<K> = keys.into_iter().collect();
+        keys.sort_by(|left, right| bstree.comparator.compare(left, right));
+
+        bstree.fill_from_sorted(keys);
+        bstree
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // self must be empty, keys in increasing order of the comparator of self
+    pub(super) fn fill_from_sorted(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+    ) {
+        debug_assert!(self.root.is_none());
+
+        let mut keys: Vec<K> = keys.into_iter().collect();
+        keys.dedup_by(|right, left| {
+            self.comparator.compare(left, right) == Ordering::Equal
+        });
+        assert!(
+            keys.windows(2).all(|pair| {
+                self.comparator.compare(&pair[0], &pair[1])
+                    == Ordering::Less
+            }),
+            "from_sorted needs the keys in increasing order"
+        );
+
+        self.len = keys.len();
+        self.root = Self::build_subtree(&mut keys.into_iter(), self.len);
+    }
+
+    // builds a balanced subtree from the next len keys of the iterator
+    fn build_subtree(
+        keys: &mut impl Iterator<Item = K>,
+        len: usize,
+    ) -> Option<NodeRc<K, A>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left_len = len / 2;
+        let left_child = Self::build_subtree(keys, left_len);
+        let data = keys.next().expect("len keys are left in the iterator");
+        let right_child = Self::build_subtree(keys, len - left_len - 1);
+
+        let node_rc =
+            Rc::new(RefCell::new(BinarySearchTreeNode::new(data)));
+        for child_rc in left_child.iter().chain(right_child.iter()) {
+            child_rc.borrow_mut().parent = Some(Rc::downgrade(&node_rc));
+        }
+
+        let mut node = node_rc.borrow_mut();
+        node.left_child = left_child;
+        node.right_child = right_child;
+        node.size = len;
+        node.summary = BinarySearchTreeNode::summary_with_childs(&node);
+        drop(node);
+
+        Some(node_rc)
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn rebalance(&mut self) {
+        // 1. tree to vine
+        let mut current_node_ptr = self.root.clone();
+        while let Some(node_rc) = current_node_ptr {
+            let has_left = node_rc.borrow().left_child.is_some();
+
+            current_node_ptr = if has_left {
+                // the left child takes the node's place, continue from there
+                Some(self.rotate_right(&node_rc))
+            } else {
+                node_rc.borrow().right_child.clone()
+            };
+        }
+
+        // 2. vine to tree. perfect_len is the biggest 2^k - 1 that fits in len.
+        let mut perfect_len = (self.len + 1).next_power_of_two();
+        if perfect_len > self.len + 1 {
+            perfect_len /= 2;
+        }
+        perfect_len -= 1;
+
+        self.compress(self.len - perfect_len);
+        while perfect_len > 1 {
+            perfect_len /= 2;
+            self.compress(perfect_len);
+        }
+    }
+
+    // left rotation on every second node of the vine, starting at the root
+    fn compress(
+        &mut self,
+        rotations: usize,
+    ) {
+        let mut current_node_ptr = self.root.clone();
+
+        for _ in 0..rotations {
+            let Some(node_rc) = current_node_ptr else {
+                break;
+            };
+
+            let new_top_rc = self.rotate_left(&node_rc);
+            current_node_ptr = new_top_rc.borrow().right_child.clone();
+        }
+    }
+
+    // the left child of node_rc takes its place, node_rc becomes its right child and gets its
+    // old right subtree as the new left subtree. Returns the node that took the place.
+    fn rotate_right(
+        &mut self,
+        node_rc: &NodeRc<K, A>,
+    ) -> NodeRc<K, A> {
+        let left_rc = node_rc
+            .borrow_mut()
+            .left_child
+            .take()
+            .expect("right rotation needs a left child");
+
+        let moved_ptr = left_rc.borrow_mut().right_child.take();
+        if let Some(moved_rc) = moved_ptr.as_ref() {
+            moved_rc.borrow_mut().parent = Some(Rc::downgrade(node_rc));
+        }
+        node_rc.borrow_mut().left_child = moved_ptr;
+
+        self.replace_in_parent(node_rc, &left_rc);
+        left_rc.borrow_mut().right_child = Some(node_rc.clone());
+        node_rc.borrow_mut().parent = Some(Rc::downgrade(&left_rc));
+
+        Self::update_rotated(node_rc, &left_rc);
+        left_rc
+    }
+
+    // mirror of rotate_right
+    fn rotate_left(
+        &mut self,
+        node_rc: &NodeRc<K, A>,
+    ) -> NodeRc<K, A> {
+        let right_rc = node_rc
+            .borrow_mut()
+            .right_child
+            .take()
+            .expect("left rotation needs a right child");
+
+        let moved_ptr = right_rc.borrow_mut().left_child.take();
+        if let Some(moved_rc) = moved_ptr.as_ref() {
+            moved_rc.borrow_mut().parent = Some(Rc::downgrade(node_rc));
+        }
+        node_rc.borrow_mut().right_child = moved_ptr;
+
+        self.replace_in_parent(node_rc, &right_rc);
+        right_rc.borrow_mut().left_child = Some(node_rc.clone());
+        node_rc.borrow_mut().parent = Some(Rc::downgrade(&right_rc));
+
+        Self::update_rotated(node_rc, &right_rc);
+        right_rc
+    }
+
+    // links new_rc where old_rc was: as the same child of old_rc's parent, or as the root
+    fn replace_in_parent(
+        &mut self,
+        old_rc: &NodeRc<K, A>,
+        new_rc: &NodeRc<K, A>,
+    ) {
+        let maybe_parent_rc = old_rc.borrow().parent_rc();
+
+        match maybe_parent_rc {
+            Some(parent_rc) => {
+                let mut parent_node = parent_rc.borrow_mut();
+                if parent_node.is_left_child(old_rc) {
+                    parent_node.left_child = Some(new_rc.clone());
+                } else {
+                    parent_node.right_child = Some(new_rc.clone());
+                }
+                new_rc.borrow_mut().parent =
+                    Some(Rc::downgrade(&parent_rc));
+            }
+            None => {
+                new_rc.borrow_mut().parent = None;
+                self.root = Some(new_rc.clone());
+            }
+        }
+    }
+
+    // a rotation keeps the keys of the rotated subtree, so only the two rotated nodes need
+    // new sizes/summaries, the lower one first
+    fn update_rotated(
+        lower_rc: &NodeRc<K, A>,
+        upper_rc: &NodeRc<K, A>,
+    ) {
+        for node_rc in [lower_rc, upper_rc] {
+            let mut node = node_rc.borrow_mut();
+            node.size =
+                1 + BinarySearchTreeNode::subtree_size(
+                    node.left_child.as_ref(),
+                ) + BinarySearchTreeNode::subtree_size(
+                    node.right_child.as_ref(),
+                );
+            node.summary = BinarySearchTreeNode::summary_with_childs(&node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod balance_tests {
+    use super::super::test_utils::Sum;
+    use super::*;
+    use proptest::prelude::*;
+
+    fn min_height(len: usize) -> usize {
+        (usize::BITS - len.leading_zeros()) as usize
+    }
+
+    #[test]
+    fn from_sorted_builds_minimum_height() {
+        for len in 0..300 {
+            let bstree = BinarySearchTree::from_sorted(0..len as i32);
+
+            assert_eq!(bstree.get_len(), len);
+            assert_eq!(bstree.validate(), Ok(()));
+            assert_eq!(bstree.height(), min_height(len));
+            assert_eq!(
+                bstree.iter().collect::<Vec<_>>(),
+                (0..len as i32).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn from_iter_and_rebalance_match_insertion() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-500i32..500, 0..400);
+
+        runner
+            .run(&strategy, |values| {
+                let collected: BinarySearchTree<i32> =
+                    values.iter().copied().collect();
+
+                let mut inserted = BinarySearchTree::new();
+                for &value in &values {
+                    inserted.insert(value);
+                }
+                inserted.rebalance();
+
+                let len = inserted.get_len();
+                prop_assert_eq!(collected.get_len(), len);
+                prop_assert_eq!(collected.validate(), Ok(()));
+                prop_assert_eq!(collected.height(), min_height(len));
+                prop_assert_eq!(inserted.validate(), Ok(()));
+                prop_assert_eq!(inserted.height(), min_height(len));
+                prop_assert_eq!(
+                    collected.iter().collect::<Vec<_>>(),
+                    inserted.iter().collect::<Vec<_>>()
+                );
+
+                // still a working tree after rebalancing
+                for value in &values {
+                    inserted.delete(value);
+                }
+                prop_assert_eq!(inserted.get_len(), 0);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn rebalance_degenerate_tree_keeps_summaries() {
+        let mut bstree: BinarySearchTree<i32, Sum> =
+            BinarySearchTree::with_augmentation();
+        for value in (1..=1000).rev() {
+            bstree.insert(value);
+        }
+        assert_eq!(bstree.validate(), Ok(()));
+        assert_eq!(bstree.height(), 1000);
+
+        bstree.rebalance();
+        assert_eq!(bstree.validate(), Ok(()));
+        assert_eq!(bstree.height(), 10);
+        assert_eq!(bstree.fold_range(..), Some(Sum(500_500)));
+        assert_eq!(bstree.fold_range(101..=200), Some(Sum(15_050)));
+        assert_eq!(bstree.select(499), Some(500));
+    }
+
+    #[test]
+    #[should_panic(expected = "increasing order")]
+    fn from_sorted_rejects_unsorted_keys() {
+        BinarySearchTree::from_sorted([1, 3, 2]);
+    }
+}
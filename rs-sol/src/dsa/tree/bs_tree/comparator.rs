@@ -0,0 +1,213 @@
+/*
+orderings other than the natural one (K: Ord). The tree takes a third type parameter C, the
+comparator, which is NaturalOrder unless the tree is built with with_comparator. insert,
+search, delete and every query that walks by key (range, neighbors, rank, split, set
+operations, validate, ...) go through it.
+    * a comparator is any Fn(&K, &K) -> Ordering (closure or fn), or a type implementing
+      Comparator. Closure parameters need their types written out (|a: &i32, b: &i32| ...),
+      otherwise the closure is not general over the lifetimes of the references.
+    * the comparator must be a total order and must not change while keys are in the tree.
+      Keys the comparator says are Equal are duplicates, only the first one is kept (for
+      case-insensitive strings, "Rust" and "rust" are the same key).
+    * trees that are built from other trees (split_off, set operations, merge) get a clone
+      of the comparator, so C: Clone there. Closures are Clone if what they capture is.
+    * helpers for the usual cases: ReverseOrder (natural order backwards) and by_key
+      (natural order of a projected field). Both are re-exported next to the tree.
+*/
+
+use std::cmp::Ordering;
+
+pub trait Comparator<K> {
+    fn compare(
+        &self,
+        left: &K,
+        right: &K,
+    ) -> Ordering;
+}
+
+// K's own Ord, the default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NaturalOrder;
+
+// K's own Ord backwards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReverseOrder;
+
+impl<K: Ord> Comparator<K> for NaturalOrder {
+    fn compare(
+        &self,
+        left: &K,
+        right: &K,
+    ) -> Ordering {
+        left.cmp(right)
+    }
+}
+
+impl<K: Ord> Comparator<K> for ReverseOrder {
+    fn compare(
+        &self,
+        left: &K,
+        right: &K,
+    ) -> Ordering {
+        right.cmp(left)
+    }
+}
+
+impl<K, F: Fn(&K, &K) -> Ordering> Comparator<K> for F {
+    fn compare(
+        &self,
+        left: &K,
+        right: &K,
+    ) -> Ordering {
+        self(left, right)
+    }
+}
+
+// orders keys by the natural order of key_fn(key), e.g. by_key(|user: &User| user.id)
+pub fn by_key<K, T: Ord>(
+    key_fn: impl Fn(&K) -> T + Clone
+) -> impl Fn(&K, &K) -> Ordering + Clone {
+    move |left, right| key_fn(left).cmp(&key_fn(right))
+}
+
+#[cfg(test)]
+mod comparator_tests {
+    use std::collections::BTreeSet;
+
+    use super::super::BinarySearchTree;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn reverse_order_matches_reversed_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec((0u8..3, -50i32..50), 0..200),
+            -60i32..60,
+        );
+
+        runner
+            .run(&strategy, |(operations, probe)| {
+                let mut bstree =
+                    BinarySearchTree::with_comparator(ReverseOrder);
+                let mut expected = BTreeSet::new();
+
+                for (operation, value) in operations {
+                    if operation == 0 {
+                        bstree.delete(&value);
+                        expected.remove(&value);
+                    } else {
+                        bstree.insert(value);
+                        expected.insert(value);
+                    }
+                }
+                prop_assert_eq!(bstree.validate(), Ok(()));
+
+                prop_assert_eq!(
+                    bstree.iter().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>()
+                );
+                prop_assert_eq!(bstree.min(), expected.last().copied());
+                // ceiling is the next key in the order of the tree, so the next smaller one
+                prop_assert_eq!(
+                    bstree.ceiling(&probe),
+                    expected.range(..=probe).next_back().copied()
+                );
+                prop_assert_eq!(
+                    bstree.successor(&probe),
+                    expected.range(..probe).next_back().copied()
+                );
+                prop_assert_eq!(
+                    bstree.range(probe..).collect::<Vec<_>>(),
+                    expected
+                        .range(..=probe)
+                        .rev()
+                        .copied()
+                        .collect::<Vec<_>>()
+                );
+                prop_assert_eq!(
+                    bstree.rank(&probe),
+                    expected.range(probe + 1..).count()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn case_insensitive_strings() {
+        let mut words =
+            BinarySearchTree::with_comparator(|a: &String, b: &String| {
+                a.to_lowercase().cmp(&b.to_lowercase())
+            });
+        for word in ["Rust", "go", "rust", "Zig", "GO", "c"] {
+            words.insert(word.to_string());
+        }
+
+        // the first spelling stays, the others were duplicates
+        assert_eq!(
+            words.iter().collect::<Vec<_>>(),
+            ["c", "go", "Rust", "Zig"]
+        );
+        assert!(words.search(&"RUST".to_string()).is_some());
+        assert!(words.depth_of(&"zig".to_string()).is_some());
+
+        words.delete(&"C".to_string());
+        assert_eq!(words.get_len(), 3);
+        assert_eq!(words.validate(), Ok(()));
+    }
+
+    #[test]
+    fn by_key_orders_by_the_projected_field() {
+        // (id, name), ordered by name only
+        let mut people = BinarySearchTree::with_comparator(by_key(
+            |person: &(u32, &str)| person.1,
+        ));
+        for person in [(3, "carol"), (1, "alice"), (2, "bob"), (4, "alice")]
+        {
+            people.insert(person);
+        }
+
+        assert_eq!(
+            people.iter().collect::<Vec<_>>(),
+            [(1, "alice"), (2, "bob"), (3, "carol")]
+        );
+        assert_eq!(people.floor(&(0, "bz")), Some((2, "bob")));
+        assert_eq!(people.select(2), Some((3, "carol")));
+    }
+
+    #[test]
+    fn derived_trees_keep_the_comparator() {
+        let mut bstree: BinarySearchTree<i32, (), ReverseOrder> =
+            (0..10).collect();
+        assert_eq!(bstree.max(), Some(0));
+
+        // split at 5 in reverse order: 9..=6 stay, 5..=0 move
+        let mut lower = bstree.split_off(&5);
+        assert_eq!(lower.iter().collect::<Vec<_>>(), [5, 4, 3, 2, 1, 0]);
+        assert_eq!(lower.comparator(), &ReverseOrder);
+
+        lower.insert(20);
+        assert_eq!(lower.min(), Some(20));
+
+        let mut odds = BinarySearchTree::with_comparator(ReverseOrder);
+        for value in [1, 3, 5, 7, 9, 11] {
+            odds.insert(value);
+        }
+        let union = &bstree | &odds;
+        assert_eq!(
+            union.iter().collect::<Vec<_>>(),
+            [11, 9, 8, 7, 6, 5, 3, 1]
+        );
+        assert_eq!((&bstree & &odds).iter().collect::<Vec<_>>(), [9, 7]);
+        assert_eq!(union.validate(), Ok(()));
+
+        bstree.merge(lower);
+        assert_eq!(
+            bstree.iter().collect::<Vec<_>>(),
+            [20, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(bstree.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,477 @@
+/*
+cursors: a position on a node of the tree that can be moved around, instead of searching from
+the root again for every key of an in-order walk or an edit.
+    * Cursor reads (shared borrow of the tree), CursorMut can also remove the key under it
+      (mutable borrow, so nothing else can change the tree while it is alive).
+    * besides the nodes there is one ghost position (no node), it sits between the max and the
+      min key. move_next from the max key goes to the ghost, move_next from the ghost goes to
+      the min key (move_prev the other way around), so a walk can go around the tree.
+    * move_next / move_prev: in-order neighbor with the parent pointers (next_in_order /
+      prev_in_order), O(height) worst case but O(1) on average over a whole walk. They return
+      false when the cursor ends up on the ghost.
+    * move_parent / move_left / move_right: follow one link. If there is no such node (or the
+      cursor is on the ghost) they return false and the cursor stays where it was.
+    * key / peek_next / peek_prev clone the key out of the node (K: Clone), like Iter.
+    * remove_current: delete_node on the node under the cursor, the cursor moves on to the
+      next key. With 2 childs delete_node swaps keys and unlinks another node, if that node was
+      the next one (successor strategy) its key is now in the current node, so the cursor
+      stays on that node.
+*/
+
+use std::rc::Rc;
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NaturalOrder, NodeRc, node_ref::NodeRef,
+};
+
+pub struct Cursor<'a, K, A = (), C = NaturalOrder> {
+    // None is the ghost position
+    current: Option<NodeRc<K, A>>,
+    tree: &'a BinarySearchTree<K, A, C>,
+}
+
+pub struct CursorMut<'a, K, A = (), C = NaturalOrder> {
+    current: Option<NodeRc<K, A>>,
+    tree: &'a mut BinarySearchTree<K, A, C>,
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn cursor_root(&self) -> Cursor<'_, K, A, C> {
+        Cursor {
+            current: self.root.clone(),
+            tree: self,
+        }
+    }
+
+    // on the min key
+    pub fn cursor_front(&self) -> Cursor<'_, K, A, C> {
+        Cursor {
+            current: self.min_node_rc(),
+            tree: self,
+        }
+    }
+
+    // on the max key
+    pub fn cursor_back(&self) -> Cursor<'_, K, A, C> {
+        Cursor {
+            current: self.max_node_rc(),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_root_mut(&mut self) -> CursorMut<'_, K, A, C> {
+        CursorMut {
+            current: self.root.clone(),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, A, C> {
+        CursorMut {
+            current: self.min_node_rc(),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, A, C> {
+        CursorMut {
+            current: self.max_node_rc(),
+            tree: self,
+        }
+    }
+
+    fn min_node_rc(&self) -> Option<NodeRc<K, A>> {
+        self.root.as_ref().map(BinarySearchTreeNode::min_of_subtree)
+    }
+
+    fn max_node_rc(&self) -> Option<NodeRc<K, A>> {
+        self.root.as_ref().map(BinarySearchTreeNode::max_of_subtree)
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // on the node of key, on the ghost position if key is not in the tree
+    pub fn cursor_at(
+        &self,
+        key: &K,
+    ) -> Cursor<'_, K, A, C> {
+        Cursor {
+            current: self.search(key),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_at_mut(
+        &mut self,
+        key: &K,
+    ) -> CursorMut<'_, K, A, C> {
+        CursorMut {
+            current: self.search(key),
+            tree: self,
+        }
+    }
+}
+
+impl<'a, K, A: Augmentation<K>, C> Cursor<'a, K, A, C> {
+    // read-only handle of the node under the cursor (node_ref.rs), None on the ghost
+    pub fn node(&self) -> Option<NodeRef<'a, K, A>> {
+        self.current.clone().map(NodeRef::new)
+    }
+
+    pub fn move_next(&mut self) -> bool {
+        self.current = self.next_node();
+        self.current.is_some()
+    }
+
+    pub fn move_prev(&mut self) -> bool {
+        self.current = self.prev_node();
+        self.current.is_some()
+    }
+
+    pub fn move_parent(&mut self) -> bool {
+        self.move_to(|node| node.parent_rc())
+    }
+
+    pub fn move_left(&mut self) -> bool {
+        self.move_to(|node| node.left_child.clone())
+    }
+
+    pub fn move_right(&mut self) -> bool {
+        self.move_to(|node| node.right_child.clone())
+    }
+
+    fn move_to(
+        &mut self,
+        target: impl FnOnce(&BinarySearchTreeNode<K, A>) -> Option<NodeRc<K, A>>,
+    ) -> bool {
+        let Some(target_rc) = self
+            .current
+            .as_ref()
+            .and_then(|node_rc| target(&node_rc.borrow()))
+        else {
+            return false;
+        };
+
+        self.current = Some(target_rc);
+        true
+    }
+
+    // the ghost position is followed by the min node and preceded by the max node
+    fn next_node(&self) -> Option<NodeRc<K, A>> {
+        match self.current.as_ref() {
+            Some(node_rc) => BinarySearchTreeNode::next_in_order(node_rc),
+            None => self.tree.min_node_rc(),
+        }
+    }
+
+    fn prev_node(&self) -> Option<NodeRc<K, A>> {
+        match self.current.as_ref() {
+            Some(node_rc) => BinarySearchTreeNode::prev_in_order(node_rc),
+            None => self.tree.max_node_rc(),
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C> Cursor<'_, K, A, C> {
+    // None on the ghost position
+    pub fn key(&self) -> Option<K> {
+        Self::key_of(self.current.as_ref())
+    }
+
+    // key move_next would land on, without moving
+    pub fn peek_next(&self) -> Option<K> {
+        Self::key_of(self.next_node().as_ref())
+    }
+
+    pub fn peek_prev(&self) -> Option<K> {
+        Self::key_of(self.prev_node().as_ref())
+    }
+
+    fn key_of(node_ptr: Option<&NodeRc<K, A>>) -> Option<K> {
+        node_ptr.map(|node_rc| node_rc.borrow().data.clone())
+    }
+}
+
+impl<K, A: Augmentation<K>, C> CursorMut<'_, K, A, C> {
+    // read-only cursor on the same position, for as long as self is not used
+    pub fn as_cursor(&self) -> Cursor<'_, K, A, C> {
+        Cursor {
+            current: self.current.clone(),
+            tree: self.tree,
+        }
+    }
+
+    pub fn move_next(&mut self) -> bool {
+        self.moved_by(|cursor| cursor.move_next())
+    }
+
+    pub fn move_prev(&mut self) -> bool {
+        self.moved_by(|cursor| cursor.move_prev())
+    }
+
+    pub fn move_parent(&mut self) -> bool {
+        self.moved_by(|cursor| cursor.move_parent())
+    }
+
+    pub fn move_left(&mut self) -> bool {
+        self.moved_by(|cursor| cursor.move_left())
+    }
+
+    pub fn move_right(&mut self) -> bool {
+        self.moved_by(|cursor| cursor.move_right())
+    }
+
+    // the moves are the same as Cursor's, the position is lent to a Cursor and taken back
+    fn moved_by(
+        &mut self,
+        step: impl FnOnce(&mut Cursor<'_, K, A, C>) -> bool,
+    ) -> bool {
+        let mut cursor = Cursor {
+            current: self.current.take(),
+            tree: self.tree,
+        };
+        let moved = step(&mut cursor);
+        self.current = cursor.current;
+
+        moved
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C> CursorMut<'_, K, A, C> {
+    pub fn key(&self) -> Option<K> {
+        self.as_cursor().key()
+    }
+
+    pub fn peek_next(&self) -> Option<K> {
+        self.as_cursor().peek_next()
+    }
+
+    pub fn peek_prev(&self) -> Option<K> {
+        self.as_cursor().peek_prev()
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    CursorMut<'_, K, A, C>
+{
+    // removes the key under the cursor and returns it, the cursor moves to the next key (the
+    // ghost position if it was the max key). Nothing happens on the ghost position.
+    pub fn remove_current(&mut self) -> Option<K> {
+        let target_rc = self.current.take()?;
+        let next_rc = BinarySearchTreeNode::next_in_order(&target_rc);
+        let removed_rc = self.tree.delete_node(&target_rc);
+
+        self.current = match next_rc {
+            Some(next_rc) if Rc::ptr_eq(&next_rc, &removed_rc) => {
+                Some(target_rc)
+            }
+            next_rc => {
+                // with 0 or 1 child the target is the removed node, the handle has to go
+                // first or take_data would clone the key instead of moving it out
+                drop(target_rc);
+                next_rc
+            }
+        };
+
+        Some(BinarySearchTreeNode::take_data(removed_rc))
+    }
+}
+
+#[cfg(test)]
+mod cursor_tests {
+    use std::collections::BTreeSet;
+
+    use super::super::{DeletionStrategy, test_utils::tree_of};
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn walks_match_iter() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-100i32..100, 0..100);
+
+        runner
+            .run(&strategy, |values| {
+                let bstree = tree_of(&values);
+                let expected: Vec<i32> = bstree.iter().collect();
+
+                let mut forward = Vec::new();
+                let mut cursor = bstree.cursor_front();
+                while let Some(key) = cursor.key() {
+                    prop_assert_eq!(
+                        cursor.peek_prev(),
+                        forward.last().copied()
+                    );
+                    forward.push(key);
+                    cursor.move_next();
+                }
+                prop_assert_eq!(&forward, &expected);
+
+                // from the ghost after the max key around to the min key
+                prop_assert_eq!(cursor.move_next(), !expected.is_empty());
+                prop_assert_eq!(cursor.key(), expected.first().copied());
+
+                let mut backward = Vec::new();
+                let mut cursor = bstree.cursor_back();
+                while let Some(key) = cursor.key() {
+                    backward.push(key);
+                    cursor.move_prev();
+                }
+                backward.reverse();
+                prop_assert_eq!(backward, expected);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn structural_moves() {
+        //       50
+        //    30    70
+        //  20  40
+        let bstree = tree_of(&[50, 30, 70, 20, 40]);
+        let mut cursor = bstree.cursor_root();
+        assert_eq!(cursor.key(), Some(50));
+        assert!(!cursor.move_parent());
+
+        assert!(cursor.move_left() && cursor.move_right());
+        assert_eq!(cursor.key(), Some(40));
+        assert_eq!(
+            (cursor.peek_prev(), cursor.peek_next()),
+            (Some(30), Some(50))
+        );
+
+        // a missing child does not move the cursor
+        assert!(!cursor.move_left() && !cursor.move_right());
+        assert_eq!(cursor.key(), Some(40));
+
+        assert!(cursor.move_parent() && cursor.move_parent());
+        assert_eq!(cursor.key(), Some(50));
+
+        let mut ghost = bstree.cursor_at(&45);
+        assert_eq!(ghost.key(), None);
+        assert!(!ghost.move_left() && !ghost.move_parent());
+        assert_eq!(
+            (ghost.peek_next(), ghost.peek_prev()),
+            (Some(20), Some(70))
+        );
+        assert!(ghost.move_prev());
+        assert_eq!(ghost.key(), Some(70));
+
+        assert_eq!(
+            BinarySearchTree::<i32>::new().cursor_root().key(),
+            None
+        );
+    }
+
+    #[test]
+    fn remove_while_walking() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategies = [
+            DeletionStrategy::Predecessor,
+            DeletionStrategy::Successor,
+            DeletionStrategy::Alternating,
+            DeletionStrategy::Random { seed: 5 },
+        ];
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..150),
+            0..strategies.len(),
+            2i32..5,
+        );
+
+        runner
+            .run(&strategy, |(values, strategy_index, divisor)| {
+                let mut bstree = tree_of(&values);
+                bstree.set_deletion_strategy(strategies[strategy_index]);
+                let mut expected: BTreeSet<i32> =
+                    values.into_iter().collect();
+
+                // one pass over the tree, removing every multiple of divisor
+                let mut removed = Vec::new();
+                let mut cursor = bstree.cursor_front_mut();
+                while let Some(key) = cursor.key() {
+                    if key % divisor == 0 {
+                        removed.extend(cursor.remove_current());
+                        prop_assert_eq!(
+                            cursor.key(),
+                            expected.range(key + 1..).next().copied()
+                        );
+                    } else {
+                        cursor.move_next();
+                    }
+                }
+
+                let expected_removed: Vec<i32> = expected
+                    .iter()
+                    .copied()
+                    .filter(|key| key % divisor == 0)
+                    .collect();
+                expected.retain(|key| key % divisor != 0);
+                prop_assert_eq!(removed, expected_removed);
+                prop_assert_eq!(bstree.validate(), Ok(()));
+                prop_assert_eq!(
+                    bstree.iter().collect::<Vec<_>>(),
+                    expected.into_iter().collect::<Vec<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn remove_at_a_key() {
+        let mut bstree = tree_of(&[50, 30, 70, 20, 40, 60, 80]);
+
+        let mut cursor = bstree.cursor_at_mut(&50);
+        assert_eq!(cursor.remove_current(), Some(50));
+        assert_eq!(cursor.key(), Some(60));
+        assert!(cursor.move_prev() && cursor.move_prev());
+        assert_eq!(cursor.key(), Some(30));
+
+        let mut cursor = bstree.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), Some(80));
+        // the max key is gone, the cursor is on the ghost now
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.remove_current(), None);
+
+        assert_eq!(bstree.iter().collect::<Vec<_>>(), [20, 30, 40, 60, 70]);
+        assert_eq!(bstree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn removed_keys_are_moved_out() {
+        // cloning one of these keys fails the test
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Unique(i32);
+
+        impl Clone for Unique {
+            fn clone(&self) -> Self {
+                panic!("{:?} was cloned", self);
+            }
+        }
+
+        for strategy in
+            [DeletionStrategy::Predecessor, DeletionStrategy::Successor]
+        {
+            let mut bstree = BinarySearchTree::new();
+            for value in [2, 1, 3] {
+                bstree.insert(Unique(value));
+            }
+            bstree.set_deletion_strategy(strategy);
+
+            // 2 has 2 childs, then 0 and 1 childs
+            // a cursor that is still around would hold on to its node
+            assert_eq!(
+                bstree.cursor_at_mut(&Unique(2)).remove_current(),
+                Some(Unique(2))
+            );
+            let mut cursor = bstree.cursor_front_mut();
+            assert_eq!(cursor.remove_current(), Some(Unique(1)));
+            assert_eq!(cursor.remove_current(), Some(Unique(3)));
+            assert_eq!(bstree.get_len(), 0);
+        }
+    }
+}
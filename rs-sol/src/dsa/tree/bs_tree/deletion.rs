@@ -0,0 +1,412 @@
+/*
+deletion strategy: which node replaces a deleted node with 2 childs (case 3 of the header).
+    * Predecessor (default): max node of the left subtree (3.a), what delete always did
+    * Successor: min node of the right subtree (3.b)
+    * Alternating: predecessor, successor, predecessor, ... one after the other
+    * Random: predecessor or successor with a coin flip (xorshift, seeded so runs repeat)
+the strategy only changes the shape, the keys of the tree are the same in every case.
+WHY: Hibbard deletion (always the same side) is not symmetric. Taking the predecessor every
+time pulls keys out of the left subtrees, after many random delete + insert pairs the right
+subtrees are bigger and the average depth slowly grows (towards sqrt(n) in Eppinger's
+experiment) instead of staying near 1.39 log2 n. Alternating or random choice mostly
+removes that drift.
+DegenerationExperiment reproduces it: a random tree of len keys, then operations pairs of
+(delete a random key, insert a new random key), so the size never changes and only the
+shape does. Height and average depth are sampled along the way.
+    `just deletion_experiment` prints the table for every strategy
+*/
+
+use super::{Augmentation, BinarySearchTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletionStrategy {
+    #[default]
+    Predecessor,
+    Successor,
+    Alternating,
+    Random {
+        seed: u64,
+    },
+}
+
+// the strategy together with what it needs to remember between deletions
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct DeletionState {
+    strategy: DeletionStrategy,
+    // alternating: the next deletion with 2 childs takes the successor
+    successor_next: bool,
+    rng: XorShift,
+}
+
+// xorshift64, good enough for coin flips and test data, not for anything else
+#[derive(Debug, Clone, Copy)]
+struct XorShift(u64);
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // a zero state would stay zero forever
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+impl DeletionState {
+    fn new(strategy: DeletionStrategy) -> Self {
+        let seed = match strategy {
+            DeletionStrategy::Random { seed } => seed,
+            _ => 0,
+        };
+
+        Self {
+            strategy,
+            successor_next: false,
+            rng: XorShift::new(seed),
+        }
+    }
+
+    // decides the replacement of one deletion with 2 childs
+    pub(super) fn use_successor(&mut self) -> bool {
+        match self.strategy {
+            DeletionStrategy::Predecessor => false,
+            DeletionStrategy::Successor => true,
+            DeletionStrategy::Alternating => {
+                self.successor_next = !self.successor_next;
+                !self.successor_next
+            }
+            DeletionStrategy::Random { .. } => self.rng.next() & 1 == 1,
+        }
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn deletion_strategy(&self) -> DeletionStrategy {
+        self.deletion.strategy
+    }
+
+    // also restarts the alternation / the random sequence
+    pub fn set_deletion_strategy(
+        &mut self,
+        strategy: DeletionStrategy,
+    ) {
+        self.deletion = DeletionState::new(strategy);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DegenerationExperiment {
+    strategy: DeletionStrategy,
+    len: usize,
+    operations: usize,
+    sample_every: usize,
+    seed: u64,
+}
+
+// shape of the tree after `operations` delete + insert pairs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExperimentSample {
+    pub operations: usize,
+    pub height: usize,
+    // internal path length / len, the average depth of a key
+    pub average_path_length: f64,
+}
+
+impl DegenerationExperiment {
+    // len^2 operations by default (capped at usize::MAX), that is where the drift shows
+    // up, a sample every len
+    pub fn new(
+        strategy: DeletionStrategy,
+        len: usize,
+    ) -> Self {
+        Self {
+            strategy,
+            len,
+            operations: len.saturating_mul(len),
+            sample_every: len.max(1),
+            seed: 0,
+        }
+    }
+
+    pub fn operations(
+        mut self,
+        operations: usize,
+    ) -> Self {
+        self.operations = operations;
+        self
+    }
+
+    pub fn sample_every(
+        mut self,
+        sample_every: usize,
+    ) -> Self {
+        self.sample_every = sample_every.max(1);
+        self
+    }
+
+    // seed of the keys and of the keys picked for deletion, same seed gives the same
+    // initial tree and the same sequence of operations for every strategy
+    pub fn seed(
+        mut self,
+        seed: u64,
+    ) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // the first sample is the initial random tree, the last one is after every operation
+    pub fn run(&self) -> Vec<ExperimentSample> {
+        let mut rng = XorShift::new(self.seed);
+        let mut bstree = BinarySearchTree::new();
+        bstree.set_deletion_strategy(self.strategy);
+        Self::insert_new_keys(&mut bstree, &mut rng, self.len);
+
+        let mut samples = vec![Self::sample(&bstree, 0)];
+        for operation in 1..=self.operations {
+            if bstree.get_len() > 0 {
+                let index = rng.next() as usize % bstree.get_len();
+                let key = bstree.select(index).expect("index is below len");
+                bstree.delete(&key);
+                Self::insert_new_keys(&mut bstree, &mut rng, 1);
+            }
+
+            if operation % self.sample_every == 0
+                || operation == self.operations
+            {
+                samples.push(Self::sample(&bstree, operation));
+            }
+        }
+
+        samples
+    }
+
+    // random keys, a key that is already in the tree is drawn again
+    fn insert_new_keys(
+        bstree: &mut BinarySearchTree<u64>,
+        rng: &mut XorShift,
+        count: usize,
+    ) {
+        let target_len = bstree.get_len() + count;
+        while bstree.get_len() < target_len {
+            bstree.insert(rng.next());
+        }
+    }
+
+    fn sample(
+        bstree: &BinarySearchTree<u64>,
+        operations: usize,
+    ) -> ExperimentSample {
+        ExperimentSample {
+            operations,
+            height: bstree.height(),
+            average_path_length: bstree.internal_path_length() as f64
+                / bstree.get_len().max(1) as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod deletion_tests {
+    use std::collections::BTreeSet;
+
+    use super::super::{EventRecorder, TreeEvent};
+    use super::*;
+    use proptest::prelude::*;
+
+    const STRATEGIES: [DeletionStrategy; 4] = [
+        DeletionStrategy::Predecessor,
+        DeletionStrategy::Successor,
+        DeletionStrategy::Alternating,
+        DeletionStrategy::Random { seed: 7 },
+    ];
+
+    #[test]
+    fn every_strategy_removes_the_right_keys() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            0..STRATEGIES.len(),
+            proptest::collection::vec((0u8..5, -50i32..50), 0..200),
+        );
+
+        runner
+            .run(&strategy, |(strategy_index, operations)| {
+                let mut bstree = BinarySearchTree::new();
+                bstree.set_deletion_strategy(STRATEGIES[strategy_index]);
+                let mut expected = BTreeSet::new();
+
+                for (operation, value) in operations {
+                    match operation {
+                        0 | 1 => {
+                            bstree.insert(value);
+                            expected.insert(value);
+                        }
+                        2 | 3 => {
+                            bstree.delete(&value);
+                            expected.remove(&value);
+                        }
+                        _ => {
+                            let removed =
+                                bstree.remove_range(value..value + 10);
+                            let before = expected.len();
+                            expected.retain(|key| {
+                                !(value..value + 10).contains(key)
+                            });
+                            prop_assert_eq!(
+                                removed,
+                                before - expected.len()
+                            );
+                        }
+                    }
+                    prop_assert_eq!(bstree.validate(), Ok(()));
+                }
+
+                prop_assert_eq!(
+                    bstree.iter().collect::<Vec<_>>(),
+                    expected.into_iter().collect::<Vec<_>>()
+                );
+                prop_assert_eq!(
+                    bstree.deletion_strategy(),
+                    STRATEGIES[strategy_index]
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    // the replacements picked for deleting the root of 20 (10 30) over and over
+    fn replacements(strategy: DeletionStrategy) -> Vec<TreeEvent<i32>> {
+        let mut bstree = BinarySearchTree::new();
+        bstree.set_deletion_strategy(strategy);
+        let mut recorder = EventRecorder::new();
+
+        for round in 0..4 {
+            for value in [20, 10, 30] {
+                bstree.insert(value + round);
+            }
+            bstree.delete_observed(&(20 + round), &mut recorder);
+            // back to an empty tree, leaves only
+            for value in [10, 20, 30] {
+                bstree.delete(&(value + round));
+            }
+        }
+
+        recorder
+            .take_events()
+            .into_iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    TreeEvent::CopiedPredecessor { .. }
+                        | TreeEvent::CopiedSuccessor { .. }
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strategies_pick_their_side() {
+        use TreeEvent::*;
+
+        assert_eq!(
+            replacements(DeletionStrategy::Successor)[..2],
+            [CopiedSuccessor { key: 30 }, CopiedSuccessor { key: 31 }]
+        );
+        assert_eq!(
+            replacements(DeletionStrategy::Alternating),
+            vec![
+                CopiedPredecessor { key: 10 },
+                CopiedSuccessor { key: 31 },
+                CopiedPredecessor { key: 12 },
+                CopiedSuccessor { key: 33 },
+            ]
+        );
+        assert_eq!(
+            replacements(DeletionStrategy::Random { seed: 3 }),
+            replacements(DeletionStrategy::Random { seed: 3 })
+        );
+
+        // the other half of a split deletes the same way
+        let mut bstree: BinarySearchTree<i32> = (0..10).collect();
+        bstree.set_deletion_strategy(DeletionStrategy::Successor);
+        let upper = bstree.split_off(&5);
+        assert_eq!(upper.deletion_strategy(), DeletionStrategy::Successor);
+    }
+
+    #[test]
+    fn experiment_keeps_the_size_and_samples() {
+        let samples =
+            DegenerationExperiment::new(DeletionStrategy::Predecessor, 64)
+                .operations(1000)
+                .sample_every(100)
+                .seed(11)
+                .run();
+
+        assert_eq!(samples.len(), 11);
+        assert_eq!(samples[0].operations, 0);
+        assert_eq!(samples[10].operations, 1000);
+        for sample in &samples {
+            // 64 keys: at least 7 levels, average depth at least that of a perfect tree
+            assert!(sample.height >= 7 && sample.height <= 64);
+            assert!(sample.average_path_length >= 4.0);
+        }
+
+        // same seed, same initial tree
+        let alternating =
+            DegenerationExperiment::new(DeletionStrategy::Alternating, 64)
+                .operations(0)
+                .seed(11)
+                .run();
+        assert_eq!(alternating, samples[..1]);
+
+        // the default number of operations does not overflow
+        let huge = DegenerationExperiment::new(
+            DeletionStrategy::Predecessor,
+            usize::MAX,
+        );
+        assert_eq!(huge.operations, usize::MAX);
+    }
+
+    // run with `just deletion_experiment`:
+    // cargo test --release deletion_experiment_report -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn deletion_experiment_report() {
+        const LEN: usize = 256;
+
+        println!("{} keys, {} delete + insert pairs", LEN, LEN * LEN);
+        println!(
+            "{:<12} {:>10} {:>8} {:>14}",
+            "strategy", "operations", "height", "avg path len"
+        );
+        for strategy in STRATEGIES {
+            let samples = DegenerationExperiment::new(strategy, LEN)
+                .sample_every(LEN * LEN / 8)
+                .run();
+
+            for sample in samples {
+                println!(
+                    "{:<12} {:>10} {:>8} {:>14.2}",
+                    format!("{:?}", strategy).split(' ').next().unwrap(),
+                    sample.operations,
+                    sample.height,
+                    sample.average_path_length
+                );
+            }
+        }
+    }
+}
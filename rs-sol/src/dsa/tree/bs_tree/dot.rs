@@ -0,0 +1,318 @@
+/*
+Graphviz DOT export: `dot -Tsvg tree.dot > tree.svg` draws the tree.
+    * nodes are written in preorder, ids are given when the parent is written (n0 is the
+      root). Labels are the keys written with {:?}, quotes and backslashes escaped for DOT.
+    * a missing child is drawn as a small point, so a lonely right child is still drawn on the
+      right side of its parent (graphviz puts the childs in the order of the edges).
+    * highlight_path(k): the nodes and edges of the search path of k are drawn in red. If k is
+      not in the tree, the path ends at the null child where k would be inserted.
+    * highlight_keys(keys): only the nodes, keys that are not in the tree are ignored.
+    * show_depth / show_size write the depth (root is 0) and the subtree size under the key.
+to_dot() gives the plain drawing, bstree.dot()... configures it, like render().
+*/
+
+use std::{
+    cell::RefCell, cmp::Ordering, collections::HashSet, fmt, rc::Rc,
+};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NaturalOrder, NodeRc,
+};
+
+const HIGHLIGHT: &str = "color=red, fontcolor=red, penwidth=2";
+
+type NodePtr<K, A> = *const RefCell<BinarySearchTreeNode<K, A>>;
+
+pub struct DotExport<'a, K, A = (), C = NaturalOrder> {
+    tree: &'a BinarySearchTree<K, A, C>,
+    highlighted_nodes: HashSet<NodePtr<K, A>>,
+    // nodes whose edge from their parent is on the search path
+    highlighted_edges: HashSet<NodePtr<K, A>>,
+    // the null child the search path ends at: (parent, is left child)
+    highlighted_null: Option<(NodePtr<K, A>, bool)>,
+    show_depth: bool,
+    show_size: bool,
+}
+
+impl<K, A, C> BinarySearchTree<K, A, C> {
+    pub fn dot(&self) -> DotExport<'_, K, A, C> {
+        DotExport {
+            tree: self,
+            highlighted_nodes: HashSet::new(),
+            highlighted_edges: HashSet::new(),
+            highlighted_null: None,
+            show_depth: false,
+            show_size: false,
+        }
+    }
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn to_dot(&self) -> String {
+        self.dot().to_string()
+    }
+}
+
+impl<K, A, C> DotExport<'_, K, A, C> {
+    pub fn show_depth(
+        mut self,
+        show_depth: bool,
+    ) -> Self {
+        self.show_depth = show_depth;
+        self
+    }
+
+    pub fn show_size(
+        mut self,
+        show_size: bool,
+    ) -> Self {
+        self.show_size = show_size;
+        self
+    }
+}
+
+impl<K, A, C: Comparator<K>> DotExport<'_, K, A, C> {
+    pub fn highlight_path(
+        mut self,
+        key: &K,
+    ) -> Self {
+        let comparator = &self.tree.comparator;
+        let mut current_node_ptr = self.tree.root.clone();
+        let mut is_root = true;
+
+        while let Some(node_rc) = current_node_ptr {
+            self.highlighted_nodes.insert(Rc::as_ptr(&node_rc));
+            if !is_root {
+                self.highlighted_edges.insert(Rc::as_ptr(&node_rc));
+            }
+            is_root = false;
+
+            let node = node_rc.borrow();
+            let go_left = match comparator.compare(key, &node.data) {
+                Ordering::Equal => break,
+                Ordering::Less => true,
+                Ordering::Greater => false,
+            };
+            let child_ptr = if go_left {
+                &node.left_child
+            } else {
+                &node.right_child
+            };
+            if child_ptr.is_none() {
+                self.highlighted_null =
+                    Some((Rc::as_ptr(&node_rc), go_left));
+            }
+            current_node_ptr = child_ptr.clone();
+        }
+
+        self
+    }
+
+    pub fn highlight_keys<'k>(
+        mut self,
+        keys: impl IntoIterator<Item = &'k K>,
+    ) -> Self
+    where
+        K: 'k,
+    {
+        let comparator = &self.tree.comparator;
+        for key in keys {
+            let mut current_node_ptr = self.tree.root.clone();
+            while let Some(node_rc) = current_node_ptr {
+                let node = node_rc.borrow();
+                let ordering = comparator.compare(key, &node.data);
+                current_node_ptr = match ordering {
+                    Ordering::Equal => {
+                        self.highlighted_nodes.insert(Rc::as_ptr(&node_rc));
+                        None
+                    }
+                    Ordering::Less => node.left_child.clone(),
+                    Ordering::Greater => node.right_child.clone(),
+                };
+            }
+        }
+
+        self
+    }
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> DotExport<'_, K, A, C> {
+    fn node_line(
+        &self,
+        id: usize,
+        node_rc: &NodeRc<K, A>,
+        depth: usize,
+    ) -> String {
+        let node = node_rc.borrow();
+        let mut label = escape(&format!("{:?}", node.data));
+        match (self.show_depth, self.show_size) {
+            (true, true) => {
+                label += &format!("\\nd={} s={}", depth, node.size)
+            }
+            (true, false) => label += &format!("\\nd={}", depth),
+            (false, true) => label += &format!("\\ns={}", node.size),
+            (false, false) => {}
+        }
+
+        if self.highlighted_nodes.contains(&Rc::as_ptr(node_rc)) {
+            format!("    n{} [label=\"{}\", {}];", id, label, HIGHLIGHT)
+        } else {
+            format!("    n{} [label=\"{}\"];", id, label)
+        }
+    }
+}
+
+// DOT strings are in double quotes, with backslash escapes
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> fmt::Display
+    for DotExport<'_, K, A, C>
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "digraph bstree {{")?;
+        writeln!(f, "    node [shape=circle];")?;
+
+        let (mut next_id, mut null_count) = (1, 0);
+        // (node, its id, depth), preorder with an explicit stack
+        let mut stack: Vec<(NodeRc<K, A>, usize, usize)> = Vec::new();
+        stack.extend(self.tree.root.clone().map(|root_rc| (root_rc, 0, 0)));
+
+        while let Some((node_rc, id, depth)) = stack.pop() {
+            writeln!(f, "{}", self.node_line(id, &node_rc, depth))?;
+
+            let node = node_rc.borrow();
+            let mut childs_to_visit = Vec::with_capacity(2);
+            for (child_ptr, is_left) in
+                [(&node.left_child, true), (&node.right_child, false)]
+            {
+                let Some(child_rc) = child_ptr else {
+                    let style = if self.highlighted_null
+                        == Some((Rc::as_ptr(&node_rc), is_left))
+                    {
+                        format!(" [{}]", HIGHLIGHT)
+                    } else {
+                        String::new()
+                    };
+                    writeln!(f, "    null{} [shape=point];", null_count)?;
+                    writeln!(
+                        f,
+                        "    n{} -> null{}{};",
+                        id, null_count, style
+                    )?;
+                    null_count += 1;
+                    continue;
+                };
+
+                let style = if self
+                    .highlighted_edges
+                    .contains(&Rc::as_ptr(child_rc))
+                {
+                    format!(" [{}]", HIGHLIGHT)
+                } else {
+                    String::new()
+                };
+                writeln!(f, "    n{} -> n{}{};", id, next_id, style)?;
+                childs_to_visit.push((
+                    child_rc.clone(),
+                    next_id,
+                    depth + 1,
+                ));
+                next_id += 1;
+            }
+
+            // the left child is written first
+            stack.extend(childs_to_visit.into_iter().rev());
+        }
+
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod dot_tests {
+    use super::super::test_utils::tree_of;
+    use super::*;
+
+    #[test]
+    fn plain_export_with_null_points() {
+        let expected = [
+            "digraph bstree {",
+            "    node [shape=circle];",
+            "    n0 [label=\"50\"];",
+            "    n0 -> n1;",
+            "    n0 -> n2;",
+            "    n1 [label=\"30\"];",
+            "    null0 [shape=point];",
+            "    n1 -> null0;",
+            "    null1 [shape=point];",
+            "    n1 -> null1;",
+            "    n2 [label=\"70\"];",
+            "    n2 -> n3;",
+            "    null2 [shape=point];",
+            "    n2 -> null2;",
+            "    n3 [label=\"60\"];",
+            "    null3 [shape=point];",
+            "    n3 -> null3;",
+            "    null4 [shape=point];",
+            "    n3 -> null4;",
+            "}",
+        ]
+        .join("\n");
+        assert_eq!(tree_of(&[50, 30, 70, 60]).to_dot(), expected);
+
+        assert_eq!(
+            BinarySearchTree::<i32>::new().to_dot(),
+            "digraph bstree {\n    node [shape=circle];\n}"
+        );
+    }
+
+    #[test]
+    fn highlights_and_annotations() {
+        let bstree = tree_of(&[50, 30, 70, 60]);
+
+        // 65 is not in the tree, the path ends at the right null child of 60
+        let dot = bstree.dot().highlight_path(&65).to_string();
+        let highlighted: Vec<&str> = dot
+            .lines()
+            .filter(|line| line.contains(HIGHLIGHT))
+            .collect();
+        assert_eq!(
+            highlighted,
+            vec![
+                "    n0 [label=\"50\", color=red, fontcolor=red, penwidth=2];",
+                "    n0 -> n2 [color=red, fontcolor=red, penwidth=2];",
+                "    n2 [label=\"70\", color=red, fontcolor=red, penwidth=2];",
+                "    n2 -> n3 [color=red, fontcolor=red, penwidth=2];",
+                "    n3 [label=\"60\", color=red, fontcolor=red, penwidth=2];",
+                "    n3 -> null4 [color=red, fontcolor=red, penwidth=2];",
+            ]
+        );
+
+        let dot = bstree
+            .dot()
+            .highlight_keys(&[30, 60, 99])
+            .show_depth(true)
+            .show_size(true)
+            .to_string();
+        assert!(dot.contains("n0 [label=\"50\\nd=0 s=4\"];"));
+        assert!(dot.contains("n3 [label=\"60\\nd=2 s=1\", color=red"));
+        assert_eq!(
+            dot.lines().filter(|line| line.contains("red")).count(),
+            2
+        );
+
+        let words: BinarySearchTree<&str> =
+            ["say \"hi\""].into_iter().collect();
+        assert!(
+            words
+                .to_dot()
+                .contains(r#"n0 [label="\"say \\\"hi\\\"\""];"#)
+        );
+    }
+}
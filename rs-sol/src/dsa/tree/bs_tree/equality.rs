@@ -0,0 +1,371 @@
+/*
+copies and comparisons of whole trees.
+    * Clone: deep copy, every node is a new node (nothing is shared with the original), parent
+      pointers point into the copy. Same shape, same sizes and summaries, same comparator and
+      deletion strategy. Built in preorder with an explicit stack, degenerate trees are fine.
+    * PartialEq / Eq: set equality, the two trees have the same keys. The shape does not
+      matter, 1 2 3 inserted in any order gives equal trees (like BTreeSet).
+    * Hash: over len and the keys in order, so equal trees hash the same.
+    * same_shape: the nodes are in the same positions, the keys are ignored. Works between
+      trees of different key types.
+    * structurally_eq: same shape and the same key on every position, the copy of a tree is
+      structurally equal to it.
+    * shape_fingerprint: a 64 bit hash of the shape (FNV-1a over the preorder child flags of
+      the nodes). The same shape always gives the same fingerprint, different fingerprints
+      mean different shapes, equal fingerprints are very likely the same shape (check with
+      same_shape to be sure). Does not depend on the keys, the process or the Rust version.
+*/
+
+use std::{
+    cell::RefCell,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use super::{Augmentation, BinarySearchTree, BinarySearchTreeNode, NodeRc};
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+// (node of the original, parent of its copy, the copy is a left child of that parent)
+type PendingCopy<K, A> = (NodeRc<K, A>, Option<NodeRc<K, A>>, bool);
+
+impl<K: Clone, A: Augmentation<K> + Clone, C: Clone> Clone
+    for BinarySearchTree<K, A, C>
+{
+    fn clone(&self) -> Self {
+        let mut copy =
+            Self::augmented_with_comparator(self.comparator.clone());
+        copy.deletion = self.deletion;
+        copy.len = self.len;
+
+        let mut stack: Vec<PendingCopy<K, A>> = self
+            .root
+            .clone()
+            .map(|root_rc| (root_rc, None, false))
+            .into_iter()
+            .collect();
+
+        while let Some((node_rc, copy_parent, is_left)) = stack.pop() {
+            let node = node_rc.borrow();
+            let copy_rc = Rc::new(RefCell::new(BinarySearchTreeNode {
+                data: node.data.clone(),
+                parent: copy_parent.as_ref().map(Rc::downgrade),
+                left_child: None,
+                right_child: None,
+                size: node.size,
+                summary: node.summary.clone(),
+            }));
+
+            match copy_parent {
+                Some(parent_rc) if is_left => {
+                    parent_rc.borrow_mut().left_child =
+                        Some(copy_rc.clone())
+                }
+                Some(parent_rc) => {
+                    parent_rc.borrow_mut().right_child =
+                        Some(copy_rc.clone())
+                }
+                None => copy.root = Some(copy_rc.clone()),
+            }
+
+            stack.extend(
+                node.right_child.clone().map(|right_rc| {
+                    (right_rc, Some(copy_rc.clone()), false)
+                }),
+            );
+            stack.extend(
+                node.left_child
+                    .clone()
+                    .map(|left_rc| (left_rc, Some(copy_rc), true)),
+            );
+        }
+
+        copy
+    }
+}
+
+impl<K: PartialEq, A: Augmentation<K>, C> PartialEq
+    for BinarySearchTree<K, A, C>
+{
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+
+        // both in order at the same time, without cloning the keys out
+        let min_of = |bstree: &Self| {
+            bstree
+                .root
+                .as_ref()
+                .map(BinarySearchTreeNode::min_of_subtree)
+        };
+        let (mut mine, mut theirs) = (min_of(self), min_of(other));
+        while let (Some(my_rc), Some(their_rc)) = (mine, theirs) {
+            if my_rc.borrow().data != their_rc.borrow().data {
+                return false;
+            }
+
+            mine = BinarySearchTreeNode::next_in_order(&my_rc);
+            theirs = BinarySearchTreeNode::next_in_order(&their_rc);
+        }
+
+        true
+    }
+}
+
+impl<K: Eq, A: Augmentation<K>, C> Eq for BinarySearchTree<K, A, C> {}
+
+impl<K: Hash, A: Augmentation<K>, C> Hash for BinarySearchTree<K, A, C> {
+    fn hash<H: Hasher>(
+        &self,
+        state: &mut H,
+    ) {
+        self.len.hash(state);
+
+        let mut current_node_ptr =
+            self.root.as_ref().map(BinarySearchTreeNode::min_of_subtree);
+        while let Some(node_rc) = current_node_ptr {
+            node_rc.borrow().data.hash(state);
+            current_node_ptr =
+                BinarySearchTreeNode::next_in_order(&node_rc);
+        }
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn same_shape<OtherK, OtherA, OtherC>(
+        &self,
+        other: &BinarySearchTree<OtherK, OtherA, OtherC>,
+    ) -> bool {
+        self.len == other.len
+            && Self::zip_preorder(
+                self.root.clone(),
+                other.root.clone(),
+                |_, _| true,
+            )
+    }
+
+    pub fn shape_fingerprint(&self) -> u64 {
+        let mut fingerprint = FNV_OFFSET_BASIS;
+        let mut stack: Vec<NodeRc<K, A>> =
+            self.root.clone().into_iter().collect();
+
+        // preorder + the childs of every node decide the shape
+        while let Some(node_rc) = stack.pop() {
+            let node = node_rc.borrow();
+            let childs = u8::from(node.left_child.is_some())
+                | (u8::from(node.right_child.is_some()) << 1);
+            fingerprint =
+                (fingerprint ^ u64::from(childs)).wrapping_mul(FNV_PRIME);
+
+            stack.extend(node.right_child.clone());
+            stack.extend(node.left_child.clone());
+        }
+
+        fingerprint
+    }
+
+    // walks both trees in preorder side by side, false as soon as the shapes differ or
+    // same_node says no for a pair of nodes on the same position
+    fn zip_preorder<OtherK, OtherA>(
+        root: Option<NodeRc<K, A>>,
+        other_root: Option<NodeRc<OtherK, OtherA>>,
+        mut same_node: impl FnMut(
+            &BinarySearchTreeNode<K, A>,
+            &BinarySearchTreeNode<OtherK, OtherA>,
+        ) -> bool,
+    ) -> bool {
+        let mut stack = vec![(root, other_root)];
+
+        while let Some(pair) = stack.pop() {
+            let (node_rc, other_rc) = match pair {
+                (None, None) => continue,
+                (Some(node_rc), Some(other_rc)) => (node_rc, other_rc),
+                _ => return false,
+            };
+
+            let (node, other) = (node_rc.borrow(), other_rc.borrow());
+            if !same_node(&node, &other) {
+                return false;
+            }
+
+            stack.push((
+                node.right_child.clone(),
+                other.right_child.clone(),
+            ));
+            stack.push((node.left_child.clone(), other.left_child.clone()));
+        }
+
+        true
+    }
+}
+
+impl<K: PartialEq, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn structurally_eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.len == other.len
+            && Self::zip_preorder(
+                self.root.clone(),
+                other.root.clone(),
+                |node, other| node.data == other.data,
+            )
+    }
+}
+
+#[cfg(test)]
+mod equality_tests {
+    use std::{collections::BTreeSet, hash::DefaultHasher};
+
+    use super::super::{
+        DeletionStrategy, ReverseOrder, test_utils::tree_of,
+    };
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hash_of(bstree: &BinarySearchTree<i32>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bstree.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..100),
+            proptest::collection::vec(-100i32..100, 0..20),
+        );
+
+        runner
+            .run(&strategy, |(values, changes)| {
+                let original = tree_of(&values);
+                let mut copy = original.clone();
+
+                prop_assert_eq!(copy.validate(), Ok(()));
+                prop_assert!(copy.structurally_eq(&original));
+                prop_assert_eq!(
+                    copy.shape_fingerprint(),
+                    original.shape_fingerprint()
+                );
+                prop_assert_eq!(&copy, &original);
+                prop_assert_eq!(hash_of(&copy), hash_of(&original));
+                if let (Some(copy_root), Some(root)) =
+                    (copy.get_root(), original.get_root())
+                {
+                    prop_assert!(!Rc::ptr_eq(copy_root, root));
+                }
+
+                // changing the copy leaves the original alone
+                for value in changes {
+                    if copy.search(&value).is_some() {
+                        copy.delete(&value);
+                    } else {
+                        copy.insert(value);
+                    }
+                }
+                prop_assert_eq!(original.validate(), Ok(()));
+                prop_assert_eq!(
+                    original.iter().collect::<BTreeSet<_>>(),
+                    values.iter().copied().collect::<BTreeSet<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn equality_ignores_the_shape() {
+        let degenerate = tree_of(&[1, 2, 3, 4, 5, 6, 7]);
+        let balanced = BinarySearchTree::from_sorted(1..=7);
+
+        assert_eq!(degenerate, balanced);
+        assert_eq!(hash_of(&degenerate), hash_of(&balanced));
+        assert!(!degenerate.same_shape(&balanced));
+        assert!(!degenerate.structurally_eq(&balanced));
+        assert_ne!(
+            degenerate.shape_fingerprint(),
+            balanced.shape_fingerprint()
+        );
+
+        assert_ne!(degenerate, tree_of(&[1, 2, 3, 4, 5, 6]));
+        assert_ne!(degenerate, tree_of(&[1, 2, 3, 4, 5, 6, 8]));
+        assert_eq!(BinarySearchTree::<i32>::new(), BinarySearchTree::new());
+    }
+
+    #[test]
+    fn same_shape_ignores_the_keys() {
+        //     2          b
+        //   1   3      a   c
+        let numbers = tree_of(&[2, 1, 3]);
+        let mut letters = BinarySearchTree::new();
+        for letter in ["b", "a", "c"] {
+            letters.insert(letter);
+        }
+        assert!(numbers.same_shape(&letters));
+        assert!(numbers.same_shape(&tree_of(&[20, 10, 30])));
+        assert!(!numbers.structurally_eq(&tree_of(&[20, 10, 30])));
+        assert_eq!(
+            numbers.shape_fingerprint(),
+            tree_of(&[20, 10, 30]).shape_fingerprint()
+        );
+
+        // a lonely left child against a lonely right child
+        assert!(!tree_of(&[2, 1]).same_shape(&tree_of(&[1, 2])));
+        assert_ne!(
+            tree_of(&[2, 1]).shape_fingerprint(),
+            tree_of(&[1, 2]).shape_fingerprint()
+        );
+    }
+
+    #[test]
+    fn clone_keeps_comparator_and_strategy() {
+        let mut reversed = BinarySearchTree::with_comparator(ReverseOrder);
+        for value in [5, 1, 9, 3] {
+            reversed.insert(value);
+        }
+        reversed.set_deletion_strategy(DeletionStrategy::Successor);
+
+        let mut copy = reversed.clone();
+        assert_eq!(copy.deletion_strategy(), DeletionStrategy::Successor);
+        copy.insert(7);
+        assert_eq!(copy.iter().collect::<Vec<_>>(), [9, 7, 5, 3, 1]);
+        assert_eq!(reversed.get_len(), 4);
+    }
+
+    #[test]
+    fn fingerprints_follow_the_shape() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(0i32..30, 0..12),
+            proptest::collection::vec(0i32..30, 0..12),
+        );
+
+        runner
+            .run(&strategy, |(values, other_values)| {
+                let (bstree, other) =
+                    (tree_of(&values), tree_of(&other_values));
+
+                if bstree.same_shape(&other) {
+                    prop_assert_eq!(
+                        bstree.shape_fingerprint(),
+                        other.shape_fingerprint()
+                    );
+                }
+                if bstree.structurally_eq(&other) {
+                    prop_assert!(
+                        bstree.same_shape(&other) && bstree == other
+                    );
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
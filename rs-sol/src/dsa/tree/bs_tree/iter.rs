@@ -0,0 +1,289 @@
+/*
+lazy in-order iterators of the binary search tree.
+    * Iter walks the tree with the parent pointers (next_in_order / prev_in_order), it does not
+      allocate a stack or a result vector. Stopping early costs nothing for the rest of the tree.
+    * both ends can be consumed (DoubleEndedIterator), remaining counts the keys between the
+      two ends, so front and back never cross each other.
+    * keys live in RefCell'd nodes, so they are cloned out while iterating (K: Clone).
+    * IntoIter consumes the tree by unlinking the min/max node at every step, which is O(1)
+      since the min node has no left child and the max node has no right child. Keys are moved
+      out of the unlinked nodes, a node that is still shared (handle from search / get_root)
+      has its key cloned instead.
+*/
+
+use std::marker::PhantomData;
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NaturalOrder, NodeRc,
+};
+
+pub struct Iter<'a, K, A = ()> {
+    front: Option<NodeRc<K, A>>,
+    back: Option<NodeRc<K, A>>,
+    remaining: usize,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+pub struct IntoIter<K, A = (), C = NaturalOrder> {
+    // field order matters: front and back must be dropped before the tree. The tree's Drop
+    // leaves nodes that are still shared alone, and the ends would count as shared.
+    front: Option<NodeRc<K, A>>,
+    back: Option<NodeRc<K, A>>,
+    tree: BinarySearchTree<K, A, C>,
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn iter(&self) -> Iter<'_, K, A> {
+        let root = self.root.as_ref();
+
+        Iter {
+            front: root.map(BinarySearchTreeNode::min_of_subtree),
+            back: root.map(BinarySearchTreeNode::max_of_subtree),
+            remaining: self.len,
+            _tree: PhantomData,
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for Iter<'_, K, A> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let front_rc = self.front.take()?;
+        self.remaining -= 1;
+        self.front = BinarySearchTreeNode::next_in_order(&front_rc);
+
+        let key = front_rc.borrow().data.clone();
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> DoubleEndedIterator for Iter<'_, K, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let back_rc = self.back.take()?;
+        self.remaining -= 1;
+        self.back = BinarySearchTreeNode::prev_in_order(&back_rc);
+
+        let key = back_rc.borrow().data.clone();
+        Some(key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> ExactSizeIterator for Iter<'_, K, A> {}
+
+impl<'a, K: Clone, A: Augmentation<K>, C> IntoIterator
+    for &'a BinarySearchTree<K, A, C>
+{
+    type Item = K;
+    type IntoIter = Iter<'a, K, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> IntoIterator
+    for BinarySearchTree<K, A, C>
+{
+    type Item = K;
+    type IntoIter = IntoIter<K, A, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let root = self.root.as_ref();
+        let front = root.map(BinarySearchTreeNode::min_of_subtree);
+        let back = root.map(BinarySearchTreeNode::max_of_subtree);
+
+        IntoIter {
+            tree: self,
+            front,
+            back,
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> IntoIter<K, A, C> {
+    // unlinks a min or max node (at most 1 child, so delete_node unlinks that node itself)
+    // and moves its key out
+    fn pop_node(
+        &mut self,
+        node_rc: NodeRc<K, A>,
+    ) -> K {
+        let removed_rc = self.tree.delete_node(&node_rc);
+        drop(node_rc);
+
+        if self.tree.len == 0 {
+            // the last node was referenced by both ends
+            self.front = None;
+            self.back = None;
+        }
+
+        BinarySearchTreeNode::take_data(removed_rc)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for IntoIter<K, A, C>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let front_rc = self.front.take()?;
+        self.front = BinarySearchTreeNode::next_in_order(&front_rc);
+
+        Some(self.pop_node(front_rc))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.tree.len, Some(self.tree.len))
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> DoubleEndedIterator
+    for IntoIter<K, A, C>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let back_rc = self.back.take()?;
+        self.back = BinarySearchTreeNode::prev_in_order(&back_rc);
+
+        Some(self.pop_node(back_rc))
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> ExactSizeIterator
+    for IntoIter<K, A, C>
+{
+}
+
+#[cfg(test)]
+mod iter_tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    fn tree_and_expected(
+        values: &[i32]
+    ) -> (BinarySearchTree<i32>, Vec<i32>) {
+        let mut bstree = BinarySearchTree::new();
+        for &value in values {
+            bstree.insert(value);
+        }
+
+        let expected = values.iter().copied().collect::<BTreeSet<_>>();
+        (bstree, expected.into_iter().collect())
+    }
+
+    #[test]
+    fn iter_gives_sorted_elements_from_both_ends() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-200i32..200, 0..300);
+
+        runner
+            .run(&strategy, |values| {
+                let (bstree, expected) = tree_and_expected(&values);
+
+                prop_assert_eq!(bstree.iter().len(), expected.len());
+                prop_assert_eq!(
+                    bstree.iter().collect::<Vec<_>>(),
+                    expected.clone()
+                );
+                prop_assert_eq!(
+                    bstree.iter().rev().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>()
+                );
+                prop_assert_eq!(
+                    bstree.into_iter().collect::<Vec<_>>(),
+                    expected
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn mixed_front_and_back_never_cross() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-200i32..200, 0..300),
+            proptest::collection::vec(any::<bool>(), 0..400),
+        );
+
+        runner
+            .run(&strategy, |(values, from_front)| {
+                let (bstree, expected) = tree_and_expected(&values);
+                let (owned_bstree, _) = tree_and_expected(&values);
+
+                let mut borrowed = bstree.iter();
+                let mut owned = owned_bstree.into_iter();
+                let mut expected = expected.into_iter();
+
+                for front in from_front {
+                    if front {
+                        let next = expected.next();
+                        prop_assert_eq!(borrowed.next(), next);
+                        prop_assert_eq!(owned.next(), next);
+                    } else {
+                        let next_back = expected.next_back();
+                        prop_assert_eq!(borrowed.next_back(), next_back);
+                        prop_assert_eq!(owned.next_back(), next_back);
+                    }
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn iter_stops_early_and_works_with_for_loops() {
+        let mut bstree = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            bstree.insert(value);
+        }
+
+        assert_eq!(
+            bstree.iter().take(3).collect::<Vec<_>>(),
+            vec![20, 30, 40]
+        );
+
+        let mut sum = 0;
+        for value in &bstree {
+            sum += value;
+        }
+        assert_eq!(sum, 350);
+
+        let empty: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert_eq!(empty.iter().next(), None);
+        assert_eq!(empty.into_iter().next_back(), None);
+    }
+
+    #[test]
+    fn into_iter_clones_keys_of_shared_nodes() {
+        let mut bstree = BinarySearchTree::new();
+        for value in ["m", "c", "x"] {
+            bstree.insert(value.to_string());
+        }
+
+        // keeps the root node alive outside of the tree
+        let shared_root = bstree.search(&"m".to_string());
+        assert_eq!(
+            bstree.into_iter().collect::<Vec<_>>(),
+            vec!["c".to_string(), "m".to_string(), "x".to_string()]
+        );
+        assert!(shared_root.is_some());
+    }
+}
@@ -0,0 +1,494 @@
+/*
+ordered map on top of the binary search tree: every node stores a key together with a value.
+STRUCTURE:
+    * the map is a BinarySearchTree whose data is a MapEntry (key + value)
+    * MapEntry is ordered by its key only, so the tree never looks at the values and all the
+      tree rules (no duplicate keys, left < node < right) hold for the keys
+    * nodes are the same Rc<RefCell<_>> nodes with Weak parent pointers as in the set
+OPERATIONS:
+    - insert: new key -> new node, existing key -> value is replaced, old value is returned
+    - get / get_mut / contains_key: same walk as search, only keys are compared
+    - remove: same as delete, the removed value is moved out of the unlinked node
+    - entry: search once, then either work on the found node or insert a new one
+NOTES:
+    - values live inside RefCell'd nodes, so get and get_mut can not hand out plain references.
+      They return ValueRef / ValueMut guards instead, which deref to the value.
+*/
+
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    cmp::Ordering,
+    fmt,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use super::{BinarySearchTree, BinarySearchTreeNode};
+
+#[derive(Debug)]
+pub struct BstMap<K, V> {
+    tree: BinarySearchTree<MapEntry<K, V>>,
+}
+
+// key/value pair stored in the tree nodes, compared by key only
+#[derive(Debug)]
+struct MapEntry<K, V> {
+    key: K,
+    value: V,
+}
+
+type EntryNodeRc<K, V> = Rc<RefCell<BinarySearchTreeNode<MapEntry<K, V>>>>;
+
+impl<K: PartialEq, V> PartialEq for MapEntry<K, V> {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq, V> Eq for MapEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for MapEntry<K, V> {
+    fn partial_cmp(
+        &self,
+        other: &Self,
+    ) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for MapEntry<K, V> {
+    fn cmp(
+        &self,
+        other: &Self,
+    ) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+// shared access to a value in the map.
+// the guard keeps its own Rc to the node, so the RefCell it borrows from can not be freed
+// while the guard is alive. 'a ties the guard to the map borrow, the map can not be changed
+// while a guard exists.
+pub struct ValueRef<'a, K, V> {
+    // field order matters: guard must be dropped before the node it borrows from
+    guard: Ref<'a, V>,
+    _node: EntryNodeRc<K, V>,
+}
+
+// mutable access to a value in the map, same idea as ValueRef
+pub struct ValueMut<'a, K, V> {
+    guard: RefMut<'a, V>,
+    _node: EntryNodeRc<K, V>,
+}
+
+impl<'a, K: 'a, V: 'a> ValueRef<'a, K, V> {
+    fn new(node: EntryNodeRc<K, V>) -> Self {
+        // SAFETY: the RefCell lives in the heap allocation of the Rc. _node keeps that
+        // allocation alive at least as long as the guard, and moving an Rc does not move the
+        // allocation, so the reference stays valid for the guard's whole life.
+        let cell: &'a RefCell<BinarySearchTreeNode<MapEntry<K, V>>> =
+            unsafe { &*Rc::as_ptr(&node) };
+
+        Self {
+            guard: Ref::map(cell.borrow(), |node| &node.data.value),
+            _node: node,
+        }
+    }
+}
+
+impl<'a, K: 'a, V: 'a> ValueMut<'a, K, V> {
+    fn new(node: EntryNodeRc<K, V>) -> Self {
+        // SAFETY: same as ValueRef::new
+        let cell: &'a RefCell<BinarySearchTreeNode<MapEntry<K, V>>> =
+            unsafe { &*Rc::as_ptr(&node) };
+
+        Self {
+            guard: RefMut::map(cell.borrow_mut(), |node| {
+                &mut node.data.value
+            }),
+            _node: node,
+        }
+    }
+}
+
+impl<K, V> Deref for ValueRef<'_, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<K, V> Deref for ValueMut<'_, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<K, V> DerefMut for ValueMut<'_, K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for ValueRef<'_, K, V> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        self.guard.fmt(f)
+    }
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for ValueMut<'_, K, V> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        self.guard.fmt(f)
+    }
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    node: EntryNodeRc<K, V>,
+    map: &'a mut BstMap<K, V>,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    map: &'a mut BstMap<K, V>,
+}
+
+impl<K, V> Default for BstMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BstMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: BinarySearchTree::new(),
+        }
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.tree.get_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.get_len() == 0
+    }
+}
+
+impl<K: Ord, V> BstMap<K, V> {
+    // returns the old value if the key was already in the map. The key itself is not updated.
+    pub fn insert(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Option<V> {
+        match self.tree.insert_node(MapEntry { key, value }) {
+            Ok(_) => None,
+            Err((existing_rc, rejected)) => Some(std::mem::replace(
+                &mut existing_rc.borrow_mut().data.value,
+                rejected.value,
+            )),
+        }
+    }
+
+    pub fn get(
+        &self,
+        key: &K,
+    ) -> Option<ValueRef<'_, K, V>> {
+        self.find_node(key).map(ValueRef::new)
+    }
+
+    pub fn get_mut(
+        &mut self,
+        key: &K,
+    ) -> Option<ValueMut<'_, K, V>> {
+        self.find_node(key).map(ValueMut::new)
+    }
+
+    pub fn contains_key(
+        &self,
+        key: &K,
+    ) -> bool {
+        self.find_node(key).is_some()
+    }
+
+    pub fn remove(
+        &mut self,
+        key: &K,
+    ) -> Option<V> {
+        let target_rc = self.find_node(key)?;
+        let removed_rc = self.tree.delete_node(&target_rc);
+        drop(target_rc);
+
+        Some(Self::take_value(removed_rc))
+    }
+
+    pub fn entry(
+        &mut self,
+        key: K,
+    ) -> Entry<'_, K, V> {
+        match self.find_node(&key) {
+            Some(node) => {
+                Entry::Occupied(OccupiedEntry { node, map: self })
+            }
+            None => Entry::Vacant(VacantEntry { key, map: self }),
+        }
+    }
+
+    fn find_node(
+        &self,
+        key: &K,
+    ) -> Option<EntryNodeRc<K, V>> {
+        self.tree.search_by(|entry| key.cmp(&entry.key))
+    }
+
+    // moves the value out of a node that delete_node unlinked.
+    // map nodes are never handed out, so after unlinking, the returned Rc is the only
+    // strong pointer left to that node.
+    fn take_value(removed_rc: EntryNodeRc<K, V>) -> V {
+        match Rc::try_unwrap(removed_rc) {
+            Ok(cell) => cell.into_inner().data.value,
+            Err(_) => unreachable!("unlinked map node is still shared"),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn or_insert(
+        self,
+        default: V,
+    ) -> ValueMut<'a, K, V> {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(
+        self,
+        default: impl FnOnce() -> V,
+    ) -> ValueMut<'a, K, V> {
+        match self {
+            Entry::Occupied(occupied) => occupied.into_mut(),
+            Entry::Vacant(vacant) => vacant.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> ValueMut<'a, K, V>
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify(
+        self,
+        f: impl FnOnce(&mut V),
+    ) -> Self {
+        match self {
+            Entry::Occupied(mut occupied) => {
+                f(&mut occupied.get_mut());
+                Entry::Occupied(occupied)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> Ref<'_, K> {
+        Ref::map(self.node.borrow(), |node| &node.data.key)
+    }
+
+    pub fn get(&self) -> Ref<'_, V> {
+        Ref::map(self.node.borrow(), |node| &node.data.value)
+    }
+
+    pub fn get_mut(&mut self) -> RefMut<'_, V> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.data.value)
+    }
+
+    pub fn into_mut(self) -> ValueMut<'a, K, V> {
+        ValueMut::new(self.node)
+    }
+
+    // replaces the value and returns the old one
+    pub fn insert(
+        &mut self,
+        value: V,
+    ) -> V {
+        std::mem::replace(&mut *self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        let removed_rc = self.map.tree.delete_node(&self.node);
+        drop(self.node);
+
+        BstMap::take_value(removed_rc)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(
+        self,
+        value: V,
+    ) -> ValueMut<'a, K, V> {
+        let entry = MapEntry {
+            key: self.key,
+            value,
+        };
+
+        match self.map.tree.insert_node(entry) {
+            Ok(inserted_rc) => ValueMut::new(inserted_rc),
+            Err(_) => {
+                unreachable!("vacant entry key is already in the map")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod map_tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum MapOp {
+        Insert(i16, i32),
+        Remove(i16),
+        Get(i16),
+        AddOrInsert(i16, i32),
+    }
+
+    fn map_op_strategy() -> impl Strategy<Value = MapOp> {
+        // narrow key range, so the same keys are hit again and again
+        let key = -50i16..50;
+        prop_oneof![
+            (key.clone(), any::<i32>())
+                .prop_map(|(k, v)| MapOp::Insert(k, v)),
+            key.clone().prop_map(MapOp::Remove),
+            key.clone().prop_map(MapOp::Get),
+            (key, -100i32..100).prop_map(|(k, v)| MapOp::AddOrInsert(k, v)),
+        ]
+    }
+
+    #[test]
+    fn behaves_like_btreemap() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(map_op_strategy(), 1..500);
+
+        runner
+            .run(&strategy, |ops| {
+                let mut map = BstMap::new();
+                let mut expected = BTreeMap::new();
+
+                for op in ops {
+                    match op {
+                        MapOp::Insert(k, v) => {
+                            prop_assert_eq!(
+                                map.insert(k, v),
+                                expected.insert(k, v)
+                            );
+                        }
+                        MapOp::Remove(k) => {
+                            prop_assert_eq!(
+                                map.remove(&k),
+                                expected.remove(&k)
+                            );
+                        }
+                        MapOp::Get(k) => {
+                            prop_assert_eq!(
+                                map.get(&k).map(|v| *v),
+                                expected.get(&k).copied()
+                            );
+                            prop_assert_eq!(
+                                map.contains_key(&k),
+                                expected.contains_key(&k)
+                            );
+                        }
+                        MapOp::AddOrInsert(k, v) => {
+                            map.entry(k)
+                                .and_modify(|old| *old += v)
+                                .or_insert(v);
+                            expected
+                                .entry(k)
+                                .and_modify(|old| *old += v)
+                                .or_insert(v);
+                        }
+                    }
+
+                    prop_assert_eq!(map.get_len(), expected.len());
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn entry_api_and_get_mut() {
+        let mut word_counts: BstMap<String, usize> = BstMap::new();
+
+        for word in
+            "the quick fox jumps over the lazy dog the end".split(' ')
+        {
+            *word_counts.entry(word.to_string()).or_insert_with(|| 0) += 1;
+        }
+
+        assert_eq!(*word_counts.get(&"the".to_string()).unwrap(), 3);
+        assert_eq!(*word_counts.get(&"fox".to_string()).unwrap(), 1);
+        assert!(word_counts.get(&"cat".to_string()).is_none());
+        assert_eq!(word_counts.get_len(), 8);
+
+        if let Some(mut count) = word_counts.get_mut(&"fox".to_string()) {
+            *count = 10;
+        }
+        assert_eq!(*word_counts.get(&"fox".to_string()).unwrap(), 10);
+
+        match word_counts.entry("the".to_string()) {
+            Entry::Occupied(occupied) => {
+                assert_eq!(*occupied.key(), "the");
+                assert_eq!(occupied.remove(), 3);
+            }
+            Entry::Vacant(_) => panic!("\"the\" must be in the map"),
+        }
+
+        assert!(!word_counts.contains_key(&"the".to_string()));
+        assert_eq!(word_counts.get_len(), 7);
+    }
+
+    #[test]
+    fn removing_two_child_node_returns_its_own_value() {
+        let mut map = BstMap::new();
+
+        for key in [50, 30, 70, 20, 40, 60, 80] {
+            map.insert(key, key * 10);
+        }
+
+        // 50 (root) and 30 both have 2 childs, their max_lsub takes their place
+        assert_eq!(map.remove(&50), Some(500));
+        assert_eq!(map.remove(&30), Some(300));
+        assert_eq!(*map.get(&40).unwrap(), 400);
+        assert_eq!(*map.get(&20).unwrap(), 200);
+        assert_eq!(map.get_len(), 5);
+    }
+}
@@ -0,0 +1,283 @@
+/*
+shape metrics, to put numbers on the CONS part of the header of mod.rs (how far a tree is
+from the balanced shape). Depth is counted in edges (the root has depth 0). height and
+min_depth count levels (nodes on the path), so an empty tree has height 0 and a single node
+has height 1.
+    * height: number of levels. min_depth: levels down to the closest leaf.
+    * internal path length I: sum of the depths of all nodes.
+    * external path length E: sum of the depths of the null childs (n + 1 of them in a tree
+      with n nodes). E = I + 2n for every binary tree.
+    * average search cost: comparisons of a successful search, averaged over the keys,
+      (I + n) / n. About 1.39 log2 n for random insertion order, (n + 1) / 2 for the
+      degenerate tree of the header.
+    * full: every node has 0 or 2 childs. complete: every level is filled, except the last
+      one which is filled from the left. perfect: every level is filled (n = 2^height - 1).
+    * balanced: the AVL condition, the heights of the two subtrees of every node differ by
+      at most one.
+    * depth histogram: number of nodes on each depth, index is the depth.
+everything except depth_of walks the tree level by level (level_nodes of traversal.rs), no
+recursion, so degenerate trees are fine.
+*/
+
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+
+use super::{Augmentation, BinarySearchTree, Comparator};
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn height(&self) -> usize {
+        self.level_nodes().count()
+    }
+
+    pub fn min_depth(&self) -> usize {
+        for (depth, level) in self.level_nodes().enumerate() {
+            if level
+                .iter()
+                .any(|node_rc| node_rc.borrow().num_of_childs() == 0)
+            {
+                return depth + 1;
+            }
+        }
+
+        0
+    }
+
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        self.level_nodes().map(|level| level.len()).collect()
+    }
+
+    pub fn internal_path_length(&self) -> usize {
+        self.depth_histogram()
+            .iter()
+            .enumerate()
+            .map(|(depth, count)| depth * count)
+            .sum()
+    }
+
+    pub fn external_path_length(&self) -> usize {
+        if self.root.is_none() {
+            // the only null child is the missing root
+            return 0;
+        }
+
+        // a node on depth d has 2 - childs null childs on depth d + 1
+        self.level_nodes()
+            .enumerate()
+            .map(|(depth, level)| {
+                let null_childs: usize = level
+                    .iter()
+                    .map(|node_rc| 2 - node_rc.borrow().num_of_childs())
+                    .sum();
+                null_childs * (depth + 1)
+            })
+            .sum()
+    }
+
+    // average number of comparisons to find a key of the tree, None for an empty tree
+    pub fn average_search_cost(&self) -> Option<f64> {
+        if self.len == 0 {
+            return None;
+        }
+
+        Some(
+            (self.internal_path_length() + self.len) as f64
+                / self.len as f64,
+        )
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.level_nodes()
+            .flatten()
+            .filter(|node_rc| node_rc.borrow().num_of_childs() == 0)
+            .count()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.level_nodes()
+            .flatten()
+            .all(|node_rc| node_rc.borrow().num_of_childs() != 1)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        // in level order, no node may come after the first missing child
+        let mut missing_seen = false;
+
+        for node_rc in self.level_nodes().flatten() {
+            let node = node_rc.borrow();
+            for child_ptr in [&node.left_child, &node.right_child] {
+                match child_ptr {
+                    Some(_) if missing_seen => return false,
+                    Some(_) => {}
+                    None => missing_seen = true,
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        let height = self.height();
+        // a degenerate tree can be higher than usize::BITS, it is not perfect anyway
+        height < usize::BITS as usize && self.len == (1usize << height) - 1
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        let levels: Vec<_> = self.level_nodes().collect();
+        // heights of the subtrees, filled from the deepest level up, so the childs of a
+        // node are always known before the node itself
+        let mut heights = HashMap::with_capacity(self.len);
+        let height_of = |heights: &HashMap<_, usize>,
+                         child_ptr: &Option<_>| {
+            child_ptr
+                .as_ref()
+                .map_or(0, |child_rc| heights[&Rc::as_ptr(child_rc)])
+        };
+
+        for node_rc in levels.iter().rev().flatten() {
+            let node = node_rc.borrow();
+            let left_height = height_of(&heights, &node.left_child);
+            let right_height = height_of(&heights, &node.right_child);
+            if left_height.abs_diff(right_height) > 1 {
+                return false;
+            }
+
+            heights.insert(
+                Rc::as_ptr(node_rc),
+                1 + left_height.max(right_height),
+            );
+        }
+
+        true
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // number of edges from the root to the node of key
+    pub fn depth_of(
+        &self,
+        key: &K,
+    ) -> Option<usize> {
+        let mut current_node_ptr = self.root.clone();
+        let mut depth = 0;
+
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            let ordering = self.comparator.compare(key, &node.data);
+            current_node_ptr = match ordering {
+                Ordering::Equal => return Some(depth),
+                Ordering::Less => node.left_child.clone(),
+                Ordering::Greater => node.right_child.clone(),
+            };
+            depth += 1;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::super::test_utils::tree_of;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn degenerate_against_balanced() {
+        // the list from the header of mod.rs
+        let degenerate = tree_of(&[100, 90, 80, 75, 70, 60, 50]);
+        assert_eq!(degenerate.height(), 7);
+        assert_eq!(degenerate.min_depth(), 7);
+        assert_eq!(degenerate.depth_of(&50), Some(6));
+        assert_eq!(degenerate.internal_path_length(), 21);
+        assert_eq!(degenerate.external_path_length(), 35);
+        assert_eq!(degenerate.average_search_cost(), Some(4.0));
+        assert_eq!(degenerate.leaf_count(), 1);
+        assert_eq!(degenerate.depth_histogram(), vec![1; 7]);
+        assert!(!degenerate.is_full());
+        assert!(!degenerate.is_complete());
+        assert!(!degenerate.is_balanced());
+
+        let balanced =
+            BinarySearchTree::from_sorted([50, 60, 70, 75, 80, 90, 100]);
+        assert_eq!(balanced.height(), 3);
+        assert_eq!(balanced.min_depth(), 3);
+        assert_eq!(balanced.depth_of(&75), Some(0));
+        assert_eq!(balanced.depth_of(&76), None);
+        assert_eq!(balanced.internal_path_length(), 10);
+        assert_eq!(balanced.average_search_cost(), Some(17.0 / 7.0));
+        assert_eq!(balanced.leaf_count(), 4);
+        assert_eq!(balanced.depth_histogram(), vec![1, 2, 4]);
+        assert!(balanced.is_full() && balanced.is_complete());
+        assert!(balanced.is_perfect() && balanced.is_balanced());
+    }
+
+    #[test]
+    fn shape_predicates() {
+        //      50
+        //    30  70
+        //  20
+        let complete = tree_of(&[50, 30, 70, 20]);
+        assert!(complete.is_complete() && complete.is_balanced());
+        assert!(!complete.is_full() && !complete.is_perfect());
+        assert_eq!(complete.min_depth(), 2);
+
+        // 40 instead of 20: the missing left child of 30 comes first
+        let not_complete = tree_of(&[50, 30, 70, 40]);
+        assert!(!not_complete.is_complete() && not_complete.is_balanced());
+
+        // full but the left subtree is 2 levels higher than the right one
+        let full = tree_of(&[50, 30, 70, 20, 40, 10, 25]);
+        assert!(full.is_full() && !full.is_balanced());
+
+        let empty = BinarySearchTree::<i32>::new();
+        assert_eq!((empty.height(), empty.min_depth()), (0, 0));
+        assert_eq!(empty.average_search_cost(), None);
+        assert!(
+            empty.is_perfect()
+                && empty.is_complete()
+                && empty.is_balanced()
+        );
+    }
+
+    #[test]
+    fn path_lengths_agree() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-200i32..200, 1..200);
+
+        runner
+            .run(&strategy, |values| {
+                let bstree = tree_of(&values);
+                let len = bstree.get_len();
+
+                let internal = bstree.internal_path_length();
+                prop_assert_eq!(
+                    bstree.external_path_length(),
+                    internal + 2 * len
+                );
+                prop_assert_eq!(
+                    values
+                        .iter()
+                        .map(|value| bstree.depth_of(value).unwrap())
+                        .max(),
+                    Some(bstree.height() - 1)
+                );
+                prop_assert_eq!(
+                    bstree.depth_histogram().iter().sum::<usize>(),
+                    len
+                );
+                prop_assert!(bstree.min_depth() <= bstree.height());
+
+                // a perfect tree is complete and a complete tree is balanced
+                prop_assert!(!bstree.is_perfect() || bstree.is_complete());
+                prop_assert!(!bstree.is_complete() || bstree.is_balanced());
+
+                let mut rebalanced = tree_of(&values);
+                rebalanced.rebalance();
+                prop_assert!(rebalanced.is_balanced());
+                prop_assert!(rebalanced.internal_path_length() <= internal);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,1284 @@
+/*
+binary search tree: used for searching.
+PROPERSTIES (also test cases):
+    - no duplicate elements (multiset.rs keeps a count per key for a bag of values)
+    - for every node, left_child < node && right_child > node (cant be equal, no duplicates)
+    - inorder traversal of bst gives elements in sorted order (increasing)
+    (sorted by K's Ord by default, comparator.rs has the option to order by something else)
+    - after deletion, that item must not be found
+    - after insertion, that value must be found
+OPERATIONS:
+    - insertion: first, check if input data is in the tree. If not, insert
+    - searching
+    - deletion: this is a bit more complicated than search & insertion, because there aare possible scenarios
+        * the node we want to delete is:
+            1. leaf node -> simply unlink the node from parent
+            2. has exactly 1 child (that child may be a sub-tree) -> child must take deleted node's place
+            3. has 2 child:
+                - 3.a: swap node with its max node in left sub-tree
+                - 3.b: swap node with its min node in right sub-tree
+              (3.a by default, deletion.rs has the option to pick 3.b, alternate or choose randomly)
+CONS:
+    - suppose we have a list: 100, 90, 80,75,70,60,50 if we create a binary search tree from this numbers
+    any search operation would be O(n) time. Since the time complexity of search operation depends on the height of
+    the tree, and this tree would add all nodes as left child of its parent, no siblings. It is like we are defining
+    a linear list and doing a linear search. We cant control this behaviour, because this is the nature of binary search treee.
+    But using other binary search tree types (self balancing trees), we can always guarantee logn search time. (AVL tree, Red-Black tree)
+    (balance.rs: from_sorted builds a balanced tree, rebalance fixes the shape of an existing one, but
+    the inserts/deletes after that can make it unbalanced again)
+    (serialize.rs writes and reads a tree with its exact shape: preorder with null markers,
+    the LeetCode level order array and a compact binary format)
+*/
+
+pub mod arena;
+pub mod augment;
+pub mod balance;
+pub mod comparator;
+pub mod cursor;
+pub mod deletion;
+pub mod dot;
+pub mod equality;
+pub mod iter;
+pub mod map;
+pub mod metrics;
+pub mod multiset;
+pub mod neighbors;
+pub mod node_ref;
+pub mod observer;
+pub mod order_statistic;
+pub mod range;
+pub mod render;
+pub mod serialize;
+pub mod set_ops;
+pub mod split;
+pub mod traversal;
+pub mod validate;
+
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    rc::{Rc, Weak},
+};
+
+use deletion::DeletionState;
+
+pub use augment::Augmentation;
+pub use comparator::{Comparator, NaturalOrder, ReverseOrder, by_key};
+pub use deletion::DeletionStrategy;
+pub use observer::{EventRecorder, TreeEvent, TreeObserver};
+
+// keys only need a total order (K: Ord) to be stored in the tree, so strings, tuples or
+// any custom id type can be used as well as plain integers.
+// A is the aggregate every node keeps about its subtree (see augment.rs), () keeps nothing.
+// C decides the order of the keys (see comparator.rs), K's own Ord by default.
+#[derive(Debug)]
+pub struct BinarySearchTree<K, A = (), C = NaturalOrder> {
+    root: Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>>,
+    len: usize,
+    // which node replaces a deleted node with 2 childs
+    deletion: DeletionState,
+    comparator: C,
+}
+
+#[derive(Debug)]
+pub struct BinarySearchTreeNode<K, A = ()> {
+    data: K,
+    // parent node could be null (for root node etc.)
+    parent: Option<Weak<RefCell<BinarySearchTreeNode<K, A>>>>,
+    left_child: Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>>,
+    right_child: Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>>,
+    // number of nodes in the subtree rooted at this node, itself included
+    size: usize,
+    // aggregate of the keys in the subtree rooted at this node, in key order
+    summary: A,
+}
+
+type NodeRc<K, A = ()> = Rc<RefCell<BinarySearchTreeNode<K, A>>>;
+
+impl<K, A: Augmentation<K>> BinarySearchTreeNode<K, A> {
+    pub fn new(data: K) -> Self {
+        Self {
+            summary: A::from_key(&data),
+            data,
+            left_child: None,
+            right_child: None,
+            parent: None,
+            size: 1,
+        }
+    }
+
+    pub fn with_parent(
+        data: K,
+        parent: Weak<RefCell<BinarySearchTreeNode<K, A>>>,
+    ) -> Self {
+        Self {
+            summary: A::from_key(&data),
+            data,
+            parent: Some(parent),
+            left_child: None,
+            right_child: None,
+            size: 1,
+        }
+    }
+
+    pub fn num_of_childs(&self) -> usize {
+        let mut count = 0;
+
+        if self.left_child.is_some() {
+            count += 1;
+        }
+        if self.right_child.is_some() {
+            count += 1;
+        }
+
+        count
+    }
+
+    // finds the maximum valued node in the left subtree of self
+    fn max_in_lsub(
+        &self
+    ) -> Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>> {
+        if self.left_child.is_some() {
+            let mut current_node = self.left_child.clone();
+
+            while let Some(node) = current_node {
+                if node.borrow().right_child.is_none() {
+                    return Some(node);
+                }
+                current_node = node.borrow().right_child.clone();
+            }
+            None
+        } else {
+            None
+        }
+    }
+
+    // finds the minimum valued node in the right subtree of self
+    fn min_in_rsub(
+        &self
+    ) -> Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>> {
+        if self.right_child.is_some() {
+            let mut current_node = self.right_child.clone();
+
+            while let Some(node) = current_node {
+                if node.borrow().left_child.is_none() {
+                    return Some(node);
+                }
+                current_node = node.borrow().left_child.clone();
+            }
+            None
+        } else {
+            None
+        }
+    }
+
+    fn parent_rc(&self) -> Option<NodeRc<K, A>> {
+        self.parent
+            .as_ref()
+            .and_then(|parent_weak| parent_weak.upgrade())
+    }
+
+    fn is_left_child(
+        &self,
+        child_rc: &NodeRc<K, A>,
+    ) -> bool {
+        self.left_child
+            .as_ref()
+            .is_some_and(|lc| Rc::ptr_eq(lc, child_rc))
+    }
+
+    fn is_right_child(
+        &self,
+        child_rc: &NodeRc<K, A>,
+    ) -> bool {
+        self.right_child
+            .as_ref()
+            .is_some_and(|rc| Rc::ptr_eq(rc, child_rc))
+    }
+
+    // minimum valued node of the subtree rooted at node_rc (node_rc itself if it has no left child)
+    fn min_of_subtree(node_rc: &NodeRc<K, A>) -> NodeRc<K, A> {
+        let mut current_rc = node_rc.clone();
+
+        loop {
+            let left_rc = current_rc.borrow().left_child.clone();
+            match left_rc {
+                Some(left_rc) => current_rc = left_rc,
+                None => return current_rc,
+            }
+        }
+    }
+
+    // maximum valued node of the subtree rooted at node_rc
+    fn max_of_subtree(node_rc: &NodeRc<K, A>) -> NodeRc<K, A> {
+        let mut current_rc = node_rc.clone();
+
+        loop {
+            let right_rc = current_rc.borrow().right_child.clone();
+            match right_rc {
+                Some(right_rc) => current_rc = right_rc,
+                None => return current_rc,
+            }
+        }
+    }
+
+    // next node in sorted order. If there is a right subtree, it is the min node of it.
+    // otherwise climb up with the parent pointers until we come up from a left child,
+    // that parent is the first node bigger than node_rc.
+    fn next_in_order(node_rc: &NodeRc<K, A>) -> Option<NodeRc<K, A>> {
+        let (min_in_rsub, mut maybe_parent_rc) = {
+            let node = node_rc.borrow();
+            (node.min_in_rsub(), node.parent_rc())
+        };
+
+        if min_in_rsub.is_some() {
+            return min_in_rsub;
+        }
+
+        let mut child_rc = node_rc.clone();
+        while let Some(parent_rc) = maybe_parent_rc {
+            if parent_rc.borrow().is_left_child(&child_rc) {
+                return Some(parent_rc);
+            }
+
+            maybe_parent_rc = parent_rc.borrow().parent_rc();
+            child_rc = parent_rc;
+        }
+
+        None
+    }
+
+    // mirror of next_in_order
+    fn prev_in_order(node_rc: &NodeRc<K, A>) -> Option<NodeRc<K, A>> {
+        let (max_in_lsub, mut maybe_parent_rc) = {
+            let node = node_rc.borrow();
+            (node.max_in_lsub(), node.parent_rc())
+        };
+
+        if max_in_lsub.is_some() {
+            return max_in_lsub;
+        }
+
+        let mut child_rc = node_rc.clone();
+        while let Some(parent_rc) = maybe_parent_rc {
+            if parent_rc.borrow().is_right_child(&child_rc) {
+                return Some(parent_rc);
+            }
+
+            maybe_parent_rc = parent_rc.borrow().parent_rc();
+            child_rc = parent_rc;
+        }
+
+        None
+    }
+
+    // moves the key out of a node that is unlinked from the tree. If the node is still shared
+    // (a handle from search / get_root is alive) the key can not be moved, it is cloned.
+    fn take_data(removed_rc: NodeRc<K, A>) -> K
+    where
+        K: Clone,
+    {
+        match Rc::try_unwrap(removed_rc) {
+            Ok(cell) => cell.into_inner().data,
+            Err(shared_rc) => shared_rc.borrow().data.clone(),
+        }
+    }
+
+    // size of an optional subtree, an empty subtree has no nodes
+    fn subtree_size(node_ptr: Option<&NodeRc<K, A>>) -> usize {
+        node_ptr.map_or(0, |node_rc| node_rc.borrow().size)
+    }
+
+    // recomputes the subtree size and summary of node_rc and of every node above it. Called
+    // with the parent of a node that is linked or unlinked, only the nodes on that path change.
+    // O(height)
+    fn update_upward(node_ptr: Option<NodeRc<K, A>>) {
+        let mut current_node_ptr = node_ptr;
+
+        while let Some(node_rc) = current_node_ptr {
+            let mut node = node_rc.borrow_mut();
+            node.size = 1
+                + Self::subtree_size(node.left_child.as_ref())
+                + Self::subtree_size(node.right_child.as_ref());
+            node.summary = Self::summary_with_childs(&node);
+            current_node_ptr = node.parent_rc();
+        }
+    }
+
+    // left subtree, the node's own key, right subtree, in this order
+    fn summary_with_childs(node: &BinarySearchTreeNode<K, A>) -> A {
+        let mut summary = A::from_key(&node.data);
+        if let Some(left_rc) = node.left_child.as_ref() {
+            summary = left_rc.borrow().summary.combine(&summary);
+        }
+        if let Some(right_rc) = node.right_child.as_ref() {
+            summary = summary.combine(&right_rc.borrow().summary);
+        }
+
+        summary
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalType {
+    InOrder,
+    PreOrder,
+    PostOrder,
+    ReverseInOrder,
+    // breadth first, level by level from left to right
+    LevelOrder,
+    // last node of every level
+    RightSideView,
+}
+
+// traversals that give their keys in groups (levels or columns), see traverse_grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupedTraversalType {
+    Levels,
+    // levels, every second level is read from right to left
+    ZigZag,
+    // columns from left to right, left child is one column left of its parent
+    Vertical,
+}
+
+impl<K, A: Augmentation<K>, C: Default> Default
+    for BinarySearchTree<K, A, C>
+{
+    fn default() -> Self {
+        Self::augmented_with_comparator(C::default())
+    }
+}
+
+impl<K> BinarySearchTree<K> {
+    pub fn new() -> Self {
+        Self::with_augmentation()
+    }
+}
+
+impl<K, A: Augmentation<K>> BinarySearchTree<K, A> {
+    // empty tree whose nodes keep an A summary of their subtree. new() is only for the plain
+    // tree (A = ()), otherwise `BinarySearchTree::new()` could not infer A.
+    pub fn with_augmentation() -> Self {
+        Self::augmented_with_comparator(NaturalOrder)
+    }
+}
+
+impl<K, C: Comparator<K>> BinarySearchTree<K, (), C> {
+    // empty tree ordered by comparator instead of K's Ord, e.g.
+    // BinarySearchTree::with_comparator(|a: &String, b: &String| a.len().cmp(&b.len()))
+    pub fn with_comparator(comparator: C) -> Self {
+        Self::augmented_with_comparator(comparator)
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn augmented_with_comparator(comparator: C) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            deletion: DeletionState::default(),
+            comparator,
+        }
+    }
+
+    // empty tree that orders and deletes like self, for trees built out of self
+    fn empty_like(&self) -> Self
+    where
+        C: Clone,
+    {
+        Self {
+            root: None,
+            len: 0,
+            deletion: self.deletion,
+            comparator: self.comparator.clone(),
+        }
+    }
+
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_root(
+        &self
+    ) -> Option<&Rc<RefCell<BinarySearchTreeNode<K, A>>>> {
+        self.root.as_ref()
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    pub fn insert(
+        &mut self,
+        insert_val: K,
+    ) {
+        // duplicates are ignored, the existing node stays as it is
+        let _ = self.insert_node(insert_val);
+    }
+
+    // insert, delete and search that report every step to observer (see observer.rs)
+    pub fn insert_observed(
+        &mut self,
+        insert_val: K,
+        observer: &mut impl TreeObserver<K>,
+    ) {
+        let _ = self.insert_node_observed(insert_val, observer);
+    }
+
+    pub fn delete_observed(
+        &mut self,
+        key: &K,
+        observer: &mut impl TreeObserver<K>,
+    ) {
+        if let Some(target_rc) = self.search_observed(key, observer) {
+            self.delete_node_observed(&target_rc, observer);
+        }
+    }
+
+    pub fn search_observed(
+        &self,
+        key: &K,
+        observer: &mut impl TreeObserver<K>,
+    ) -> Option<NodeRc<K, A>> {
+        let mut current_node_ptr = self.root.clone();
+
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            observer.on_event(TreeEvent::Compared { key: &node.data });
+
+            let ordering = self.comparator.compare(key, &node.data);
+            current_node_ptr = match ordering {
+                Ordering::Equal => {
+                    observer.on_event(TreeEvent::Found { key: &node.data });
+                    drop(node);
+                    return Some(node_rc);
+                }
+                Ordering::Less => {
+                    observer.on_event(TreeEvent::WentLeft);
+                    node.left_child.clone()
+                }
+                Ordering::Greater => {
+                    observer.on_event(TreeEvent::WentRight);
+                    node.right_child.clone()
+                }
+            };
+        }
+
+        observer.on_event(TreeEvent::NotFound);
+        None
+    }
+
+    // inserts the value and returns its newly created node. If an equal value already exists
+    // nothing is inserted, the existing node is returned together with the rejected value
+    // (BstMap uses it to replace the value of an existing key)
+    fn insert_node(
+        &mut self,
+        insert_val: K,
+    ) -> Result<NodeRc<K, A>, (NodeRc<K, A>, K)> {
+        self.insert_node_observed(insert_val, &mut ())
+    }
+
+    fn insert_node_observed(
+        &mut self,
+        insert_val: K,
+        observer: &mut impl TreeObserver<K>,
+    ) -> Result<NodeRc<K, A>, (NodeRc<K, A>, K)> {
+        let mut inserted_rc = None;
+
+        if self.root.is_none() {
+            // no node in the tree, this node will be the root node
+            let new_rc = Rc::new(RefCell::new(BinarySearchTreeNode::new(
+                insert_val,
+            )));
+            observer.on_event(TreeEvent::InsertedLeaf {
+                key: &new_rc.borrow().data,
+                parent: None,
+            });
+            self.root = Some(new_rc.clone());
+            inserted_rc = Some(new_rc);
+        } else {
+            let mut current_node_ptr = self.root.clone();
+
+            while let Some(ref node_rc) = current_node_ptr {
+                // the reason of this block expression:
+                // since rust does not allow mutating a value if that value is borrowed (immutable/mutable),
+                // if i try to assign current_node_ref value in the same scope with current_node
+                // the code wont compile because current_node_ref is borrowed (the while let line (ref node_rc)).
+                // i can assign outside of the scope because of Non LExical Lifetimes (NLL)
+                // the compiler will understand that if node_rc borrow is not used after some point,
+                // we can assume that borrow's lifetime is ended at that point.
+                // so we can say that sometimes lifetimes are not bound by scopes.
+                current_node_ptr = {
+                    let mut current_node = node_rc.borrow_mut();
+                    observer.on_event(TreeEvent::Compared {
+                        key: &current_node.data,
+                    });
+                    let ordering = self
+                        .comparator
+                        .compare(&insert_val, &current_node.data);
+                    if ordering == Ordering::Equal {
+                        // node already exists in the tree, no need to make an insertion
+                        observer.on_event(TreeEvent::Found {
+                            key: &current_node.data,
+                        });
+                        return Err((node_rc.clone(), insert_val));
+                    } else if ordering == Ordering::Less {
+                        observer.on_event(TreeEvent::WentLeft);
+                        if current_node.left_child.is_none() {
+                            // there is no left child, means we should insert the input data as
+                            // left child of this node, this same control must be done for right child as well
+                            // if there is a left child, move pointer to that child
+                            let new_rc = Rc::new(RefCell::new(
+                                BinarySearchTreeNode::with_parent(
+                                    insert_val,
+                                    Rc::downgrade(node_rc),
+                                ),
+                            ));
+
+                            observer.on_event(TreeEvent::InsertedLeaf {
+                                key: &new_rc.borrow().data,
+                                parent: Some(&current_node.data),
+                            });
+                            current_node.left_child = Some(new_rc.clone());
+                            inserted_rc = Some(new_rc);
+                            break;
+                        }
+
+                        current_node.left_child.clone()
+                    } else {
+                        observer.on_event(TreeEvent::WentRight);
+                        if current_node.right_child.is_none() {
+                            let new_rc = Rc::new(RefCell::new(
+                                BinarySearchTreeNode::with_parent(
+                                    insert_val,
+                                    Rc::downgrade(node_rc),
+                                ),
+                            ));
+
+                            observer.on_event(TreeEvent::InsertedLeaf {
+                                key: &new_rc.borrow().data,
+                                parent: Some(&current_node.data),
+                            });
+                            current_node.right_child = Some(new_rc.clone());
+                            inserted_rc = Some(new_rc);
+                            break;
+                        }
+
+                        current_node.right_child.clone()
+                    }
+                };
+            }
+        }
+
+        self.len += 1;
+
+        // the loop only ends with a break (after linking the new node) or a return
+        // for an existing value, so there is always an inserted node here
+        let inserted_rc =
+            inserted_rc.expect("insert loop ended without linking a node");
+
+        // every node on the path from the root got one more node below it
+        let parent_rc = inserted_rc.borrow().parent_rc();
+        BinarySearchTreeNode::update_upward(parent_rc);
+
+        Ok(inserted_rc)
+    }
+
+    pub fn delete(
+        &mut self,
+        key: &K,
+    ) {
+        let target_rc = match self.search(key) {
+            Some(target_rc) => target_rc,
+            None => return,
+        };
+
+        self.delete_node(&target_rc);
+    }
+
+    // returns the node that is physically unlinked from the tree. For a target with 2 childs
+    // this is not the target itself but its max_lsub (or min_rsub) node, which carries the
+    // removed key after the swap (see case 2). The returned node has no parent and no childs anymore.
+    fn delete_node(
+        &mut self,
+        target_rc: &Rc<RefCell<BinarySearchTreeNode<K, A>>>,
+    ) -> NodeRc<K, A> {
+        self.delete_node_observed(target_rc, &mut ())
+    }
+
+    fn delete_node_observed(
+        &mut self,
+        target_rc: &NodeRc<K, A>,
+        observer: &mut impl TreeObserver<K>,
+    ) -> NodeRc<K, A> {
+        // this method should remove the node, and make necesaarry bindings between parent and child (target's child)
+
+        let (maybe_parent_weak, num_of_childs) = {
+            let target_node = target_rc.borrow();
+            let num_of_childs = target_node.num_of_childs();
+            observer.on_event(TreeEvent::DeletionCase {
+                key: &target_node.data,
+                num_of_childs,
+            });
+            (target_node.parent.clone(), num_of_childs)
+        };
+
+        match num_of_childs {
+            0 => {
+                if let Some(parent_rc) = maybe_parent_weak
+                    .and_then(|parent_weak| parent_weak.upgrade())
+                {
+                    // target node is not the root node, and it is a leaf node in the tree
+                    // so we should remove the link from its parent, and it will be dropped
+                    // we need to find the direction from parent as well
+                    let mut parent_node = parent_rc.borrow_mut();
+
+                    if parent_node
+                        .left_child
+                        .as_ref()
+                        .is_some_and(|lc| Rc::ptr_eq(lc, target_rc))
+                    {
+                        parent_node.left_child = None;
+                    } else {
+                        parent_node.right_child = None;
+                    }
+                } else {
+                    // target node has no parent node, so it is the root node with zero childs
+                    // so the tree has only the root node. Setting root node to None will be enough
+                    self.root = None;
+                }
+
+                self.len -= 1;
+                // every node above the target lost one node below it
+                let parent_rc = target_rc.borrow().parent_rc();
+                BinarySearchTreeNode::update_upward(parent_rc);
+                target_rc.borrow_mut().parent = None;
+
+                target_rc.clone()
+            }
+            1 => {
+                let child_rc = target_rc
+                    .borrow()
+                    .left_child
+                    .clone()
+                    .or(target_rc.borrow().right_child.clone())
+                    .unwrap();
+
+                if let Some(parent_rc) = maybe_parent_weak
+                    .and_then(|parent_weak| parent_weak.upgrade())
+                {
+                    // target is a non-root node in the tree and has 1 child.
+                    // we must find the target's direction from its parent.
+                    // and bind parent to grandchild in that direction and update the parent ptr
+                    // of the grandchild to its new parent
+                    let mut parent_node = parent_rc.borrow_mut();
+
+                    if parent_node
+                        .left_child
+                        .as_ref()
+                        .is_some_and(|lc| Rc::ptr_eq(lc, target_rc))
+                    {
+                        parent_node.left_child = Some(child_rc.clone());
+                    } else {
+                        parent_node.right_child = Some(child_rc.clone());
+                    }
+
+                    child_rc.borrow_mut().parent =
+                        Some(Rc::downgrade(&parent_rc));
+                } else {
+                    // root node with 1 child. Find the child's direction, New root will be
+                    // this child and child's parent will be None
+                    child_rc.borrow_mut().parent = None;
+                    self.root = Some(child_rc);
+                }
+
+                self.len -= 1;
+                let parent_rc = target_rc.borrow().parent_rc();
+                BinarySearchTreeNode::update_upward(parent_rc);
+
+                // target is out of the tree now, drop its links so it does not keep
+                // pointing into the tree
+                let mut target_node = target_rc.borrow_mut();
+                target_node.parent = None;
+                target_node.left_child = None;
+                target_node.right_child = None;
+                drop(target_node);
+
+                target_rc.clone()
+            }
+            2 => {
+                // in this scenario, we must need to find max_lsub or min_rsub nodes.
+                // the deletion strategy decides which one (max_lsub by default)
+                let use_successor = self.deletion.use_successor();
+                let replacement_rc = if use_successor {
+                    target_rc.borrow().min_in_rsub().unwrap()
+                } else {
+                    target_rc.borrow().max_in_lsub().unwrap()
+                };
+
+                // keys are not Copy anymore, so instead of copying max_lsub's data into target
+                // the two keys are swapped. target ends up holding max_lsub's key (which keeps the
+                // ordering valid) and the key we want to remove goes away together with max_lsub.
+                // same for min_rsub.
+                std::mem::swap(
+                    &mut target_rc.borrow_mut().data,
+                    &mut replacement_rc.borrow_mut().data,
+                );
+                let target_node = target_rc.borrow();
+                let key = &target_node.data;
+                observer.on_event(if use_successor {
+                    TreeEvent::CopiedSuccessor { key }
+                } else {
+                    TreeEvent::CopiedPredecessor { key }
+                });
+                drop(target_node);
+
+                // remove this node, identified by Rc pointer. target is on the path from
+                // max_lsub/min_rsub to the root, so its size is updated by that call as well
+                self.delete_node_observed(&replacement_rc, observer)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // the node handle is opaque outside this module, cursor_at (cursor.rs) gives a position
+    // on it that can read the key and move around, node_ref (node_ref.rs) a read-only view
+    pub fn search(
+        &self,
+        key: &K,
+    ) -> Option<Rc<RefCell<BinarySearchTreeNode<K, A>>>> {
+        self.search_by(|data| self.comparator.compare(key, data))
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    // same walk as search, but the direction is decided by probe. probe compares the searched
+    // thing with a node's data, so BstMap can search its entries with a bare key.
+    fn search_by(
+        &self,
+        probe: impl Fn(&K) -> Ordering,
+    ) -> Option<NodeRc<K, A>> {
+        if self.root.is_none() {
+            // no node in the tree
+            None
+        } else {
+            let mut current_node_ptr = self.root.clone();
+
+            while let Some(ref_node_rc) = current_node_ptr {
+                let ref_current_node = ref_node_rc.borrow();
+
+                match probe(&ref_current_node.data) {
+                    Ordering::Equal => return Some(ref_node_rc.clone()),
+                    Ordering::Less => {
+                        current_node_ptr =
+                            ref_current_node.left_child.clone();
+                    }
+                    Ordering::Greater => {
+                        current_node_ptr =
+                            ref_current_node.right_child.clone();
+                    }
+                }
+            }
+
+            None
+        }
+    }
+}
+
+// the walks only take a root, the order of the tree does not matter for them. Keeping C out
+// of the impl lets `BinarySearchTree::traverse_inorder_print(..)` infer the type.
+impl<K, A: Augmentation<K>> BinarySearchTree<K, A> {
+    // the walks below use an explicit stack instead of recursion. A recursive walk needs one
+    // stack frame per level, and a degenerate tree (sorted input, see the header) has as many
+    // levels as nodes, which overflows the call stack for big trees.
+    fn for_each_inorder(
+        root: Option<&NodeRc<K, A>>,
+        mut f: impl FnMut(&K),
+    ) {
+        let mut stack: Vec<NodeRc<K, A>> = Vec::new();
+        let mut current_node_ptr = root.cloned();
+
+        while current_node_ptr.is_some() || !stack.is_empty() {
+            // go as left as possible, remembering the nodes on the way
+            while let Some(node_rc) = current_node_ptr {
+                current_node_ptr = node_rc.borrow().left_child.clone();
+                stack.push(node_rc);
+            }
+
+            if let Some(node_rc) = stack.pop() {
+                let node = node_rc.borrow();
+                f(&node.data);
+                current_node_ptr = node.right_child.clone();
+            }
+        }
+    }
+
+    fn for_each_preorder(
+        root: Option<&NodeRc<K, A>>,
+        mut f: impl FnMut(&K),
+    ) {
+        let mut stack: Vec<NodeRc<K, A>> =
+            root.into_iter().cloned().collect();
+
+        while let Some(node_rc) = stack.pop() {
+            let node = node_rc.borrow();
+            f(&node.data);
+
+            // right child is pushed first, so the left one is popped first
+            stack.extend(node.right_child.clone());
+            stack.extend(node.left_child.clone());
+        }
+    }
+
+    fn for_each_postorder(
+        root: Option<&NodeRc<K, A>>,
+        mut f: impl FnMut(&K),
+    ) {
+        // the bool tells if the childs of the node are already pushed to the stack.
+        // a node is visited on its second pop, after its whole subtree.
+        let mut stack: Vec<(NodeRc<K, A>, bool)> = root
+            .into_iter()
+            .map(|node_rc| (node_rc.clone(), false))
+            .collect();
+
+        while let Some((node_rc, childs_pushed)) = stack.pop() {
+            if childs_pushed {
+                f(&node_rc.borrow().data);
+                continue;
+            }
+
+            let node = node_rc.borrow();
+            let (left_rc, right_rc) =
+                (node.left_child.clone(), node.right_child.clone());
+            drop(node);
+
+            stack.push((node_rc, true));
+            stack.extend(right_rc.map(|right_rc| (right_rc, false)));
+            stack.extend(left_rc.map(|left_rc| (left_rc, false)));
+        }
+    }
+}
+
+impl<K: std::fmt::Debug, A: Augmentation<K>> BinarySearchTree<K, A> {
+    pub fn traverse_inorder_print(
+        root: Option<&Rc<RefCell<BinarySearchTreeNode<K, A>>>>
+    ) {
+        Self::for_each_inorder(root, |data| println!("{:?}", data));
+    }
+
+    pub fn traverse_preorder_print(
+        root: Option<&Rc<RefCell<BinarySearchTreeNode<K, A>>>>
+    ) {
+        Self::for_each_preorder(root, |data| println!("{:?}", data));
+    }
+
+    pub fn traverse_postorder_print(
+        root: Option<&Rc<RefCell<BinarySearchTreeNode<K, A>>>>
+    ) {
+        Self::for_each_postorder(root, |data| println!("{:?}", data));
+    }
+}
+
+// every node owns its childs through Rc, so the default drop of the root drops the childs
+// from inside the drop of their parent, recursively. For a degenerate tree that is one stack
+// frame per node, a few hundred thousand nodes are enough to overflow the stack.
+// Instead, childs are taken out of their parent before the parent is dropped, so every node
+// is dropped with no childs left in it.
+impl<K, A, C> Drop for BinarySearchTree<K, A, C> {
+    fn drop(&mut self) {
+        let mut stack: Vec<NodeRc<K, A>> =
+            self.root.take().into_iter().collect();
+
+        while let Some(node_rc) = stack.pop() {
+            // a node still shared through a handle (search / get_root) is not dropped here,
+            // it keeps its subtree alive for whoever holds that handle
+            if Rc::strong_count(&node_rc) == 1 {
+                let mut node = node_rc.borrow_mut();
+                stack.extend(node.left_child.take());
+                stack.extend(node.right_child.take());
+            }
+        }
+    }
+}
+
+// fixtures for the test modules of the files above
+#[cfg(test)]
+mod test_utils {
+    use super::{Augmentation, BinarySearchTree};
+
+    // the keys are inserted one by one, their order decides the shape (collect would give
+    // a balanced tree)
+    pub(super) fn tree_of(values: &[i32]) -> BinarySearchTree<i32> {
+        let mut bstree = BinarySearchTree::new();
+        for &value in values {
+            bstree.insert(value);
+        }
+
+        bstree
+    }
+
+    //            50
+    //        30      70
+    //      20  40  60  80
+    //                65
+    pub(super) fn sample_tree() -> BinarySearchTree<i32> {
+        tree_of(&[50, 30, 70, 20, 40, 60, 80, 65])
+    }
+
+    // sum of the keys in a subtree
+    #[derive(Debug, PartialEq)]
+    pub(super) struct Sum(pub(super) i64);
+
+    impl Augmentation<i32> for Sum {
+        fn from_key(key: &i32) -> Self {
+            Sum(*key as i64)
+        }
+
+        fn combine(
+            &self,
+            other: &Self,
+        ) -> Self {
+            Sum(self.0 + other.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod binary_tree_tests {
+    use std::{collections::HashSet, fmt::Debug, hash::Hash};
+
+    use super::*;
+    use proptest::{prelude::*, strategy::Strategy};
+
+    // custom id type, only derives what the tree needs (Ord) and what the tests need
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct UserId(u32);
+
+    fn unique_in_insertion_order<K: Clone + Eq + Hash>(
+        values: &[K]
+    ) -> Vec<K> {
+        let mut seen_values = HashSet::new();
+        let mut unique_values = Vec::new();
+
+        for value in values {
+            if seen_values.insert(value.clone()) {
+                unique_values.push(value.clone());
+            }
+        }
+
+        unique_values
+    }
+
+    fn inorder_traversal_gives_sorted_elements<K>(
+        strategy: impl Strategy<Value = K>
+    ) where
+        K: Ord + Clone + Debug + Hash,
+    {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(strategy, 1..500);
+
+        runner
+            .run(&strategy, |values| {
+                let mut bstree = BinarySearchTree::new();
+
+                for value in &values {
+                    bstree.insert(value.clone());
+                }
+
+                let maybe_inorder_vec =
+                    bstree.traverse(TraversalType::InOrder);
+                if let Some(inorder_vec) = maybe_inorder_vec {
+                    let mut sorted = unique_in_insertion_order(&values);
+                    sorted.sort();
+                    prop_assert_eq!(inorder_vec, sorted);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn no_duplicate_elements<K>(strategy: impl Strategy<Value = K>)
+    where
+        K: Ord + Clone + Debug + Hash,
+    {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(strategy, 1..500);
+
+        runner
+            .run(&strategy, |values| {
+                let mut bstree = BinarySearchTree::new();
+
+                for value in &values {
+                    bstree.insert(value.clone());
+                }
+
+                let unique_values = unique_in_insertion_order(&values);
+                prop_assert!(bstree.validate().is_ok());
+
+                prop_assert_eq!(
+                    bstree.get_len(),
+                    unique_values.len(),
+                    "bstree get_len() must match number of unique elements"
+                );
+
+                for unique_value in &unique_values {
+                    let len_before_deletion = bstree.get_len();
+                    bstree.delete(unique_value);
+
+                    prop_assert_eq!(
+                        bstree.get_len(),
+                        len_before_deletion - 1
+                    );
+
+                    prop_assert!(
+                        bstree.search(unique_value).is_none(),
+                        "No duplicate elements should be present"
+                    );
+                }
+
+                prop_assert_eq!(bstree.get_len(), 0);
+
+                for unique_value in &unique_values {
+                    prop_assert!(bstree.search(unique_value).is_none());
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn inserted_node_should_be_found<K>(
+        strategy: impl Strategy<Value = K> + Clone
+    ) where
+        K: Ord + Clone + Debug + Hash,
+    {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(strategy.clone(), 1..500),
+            strategy,
+        );
+
+        runner
+            .run(&strategy, |(values, probe)| {
+                // create the bst, and insert the values
+                let mut bstree = BinarySearchTree::new();
+
+                for value in &values {
+                    bstree.insert(value.clone());
+                }
+
+                let unique_values_in_tree = unique_in_insertion_order(&values);
+
+                for unique_value in &unique_values_in_tree {
+                    prop_assert!(
+                        bstree.search(unique_value).is_some(),
+                        "value {:?} should be present in the tree",
+                        unique_value
+                    );
+                }
+
+                // a random probe is found if and only if it was inserted
+                prop_assert_eq!(
+                    bstree.search(&probe).is_some(),
+                    unique_values_in_tree.contains(&probe),
+                    "probe {:?} found state does not match the inserted values",
+                    probe
+                );
+
+                prop_assert_eq!(bstree.get_len(),
+                    unique_values_in_tree.len(),
+                    "get_len MUST be the same len with unique_values_in_tree"
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn deleted_nodes_should_not_be_found<K>(
+        strategy: impl Strategy<Value = K>
+    ) where
+        K: Ord + Clone + Debug + Hash,
+    {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(strategy, 1..500);
+
+        runner
+            .run(&strategy, |values| {
+                let mut bstree = BinarySearchTree::new();
+
+                for value in &values {
+                    bstree.insert(value.clone());
+                }
+
+                // create a list of unique values from tree
+                let unique_values_in_tree = unique_in_insertion_order(&values);
+
+                // from unique values, select some nodes to dolete
+                let nodes_to_delete: Vec<K> = unique_values_in_tree
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| i % 2 == 0)
+                    .map(|(_, v)| v.clone())
+                    .collect();
+
+                for node_to_delete in &nodes_to_delete {
+                    bstree.delete(node_to_delete);
+                }
+                prop_assert!(bstree.validate().is_ok());
+
+                // this checks the deleted node is in the tree or not
+                for node in &nodes_to_delete {
+                    prop_assert!(
+                        bstree.search(node).is_none(),
+                        "deleted value {:?} should not be found",
+                        node
+                    );
+                }
+
+                // find the remaining unique nodes
+                let remaining_nodes_after_deletion: Vec<K> =
+                    unique_values_in_tree
+                        .into_iter()
+                        .filter(|node| !nodes_to_delete.contains(node))
+                        .collect();
+
+                for node in &remaining_nodes_after_deletion {
+                    prop_assert!(
+                        bstree.search(node).is_some(),
+                        "non deleted value {:?} should still be found",
+                        node
+                    );
+                }
+
+                prop_assert_eq!(
+                    bstree.get_len(),
+                    remaining_nodes_after_deletion.len(),
+                    "bstree get_len() must match number of remaining unique elements"
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    // small alphabet and length, so duplicates show up often
+    fn string_strategy() -> impl Strategy<Value = String> + Clone {
+        "[a-d]{0,4}"
+    }
+
+    // builds the tree sorted descending input would give (every node is the left child of the
+    // previous one) in O(n), inserting would walk the whole chain for every key
+    fn degenerate_tree(len: i32) -> BinarySearchTree<i32> {
+        let root_rc =
+            Rc::new(RefCell::new(BinarySearchTreeNode::new(len - 1)));
+        root_rc.borrow_mut().size = len as usize;
+        let mut current_rc = root_rc.clone();
+
+        for key in (0..len - 1).rev() {
+            let child_rc =
+                Rc::new(RefCell::new(BinarySearchTreeNode::with_parent(
+                    key,
+                    Rc::downgrade(&current_rc),
+                )));
+            // keys below this one are 0..key
+            child_rc.borrow_mut().size = key as usize + 1;
+            current_rc.borrow_mut().left_child = Some(child_rc.clone());
+            current_rc = child_rc;
+        }
+
+        BinarySearchTree {
+            root: Some(root_rc),
+            len: len as usize,
+            deletion: DeletionState::default(),
+            comparator: NaturalOrder,
+        }
+    }
+
+    #[test]
+    fn degenerate_tree_with_10_million_nodes_is_stack_safe() {
+        const LEN: i32 = 10_000_000;
+        let mut bstree = degenerate_tree(LEN);
+        assert_eq!(bstree.validate(), Ok(()));
+
+        assert_eq!(bstree.iter().next(), Some(0));
+        assert_eq!(bstree.iter().next_back(), Some(LEN - 1));
+        assert_eq!(bstree.iter().count(), LEN as usize);
+        assert_eq!(bstree.preorder_iter().count(), LEN as usize);
+        assert_eq!(bstree.postorder_iter().count(), LEN as usize);
+        assert_eq!(bstree.right_side_view_iter().count(), LEN as usize);
+        assert_eq!(
+            bstree
+                .traverse(TraversalType::LevelOrder)
+                .map(|keys| keys.len()),
+            Some(LEN as usize)
+        );
+
+        let mut count = 0;
+        BinarySearchTree::for_each_inorder(bstree.get_root(), |_| {
+            count += 1
+        });
+        BinarySearchTree::for_each_preorder(bstree.get_root(), |_| {
+            count += 1
+        });
+        BinarySearchTree::for_each_postorder(bstree.get_root(), |_| {
+            count += 1
+        });
+        assert_eq!(count, 3 * LEN as usize);
+
+        // walks the whole chain down to the deepest node
+        bstree.insert(-1);
+        assert!(bstree.search(&-1).is_some());
+        bstree.delete(&0);
+        assert_eq!(bstree.get_len(), LEN as usize);
+        assert_eq!(bstree.validate(), Ok(()));
+
+        drop(bstree);
+
+        // a partly consumed IntoIter drops the rest of the tree as well
+        let mut into_iter = degenerate_tree(LEN).into_iter();
+        assert_eq!(into_iter.next_back(), Some(LEN - 1));
+        drop(into_iter);
+    }
+
+    // runs the whole property suite above for a single key type
+    macro_rules! key_type_suite {
+        ($suite:ident, $key:ty, $strategy:expr) => {
+            mod $suite {
+                use super::*;
+
+                #[test]
+                fn inorder_traversal_gives_sorted_elements() {
+                    super::inorder_traversal_gives_sorted_elements::<$key>(
+                        $strategy,
+                    );
+                }
+
+                #[test]
+                fn no_duplicate_elements() {
+                    super::no_duplicate_elements::<$key>($strategy);
+                }
+
+                #[test]
+                fn inserted_node_should_be_found() {
+                    super::inserted_node_should_be_found::<$key>($strategy);
+                }
+
+                #[test]
+                fn deleted_nodes_should_not_be_found() {
+                    super::deleted_nodes_should_not_be_found::<$key>(
+                        $strategy,
+                    );
+                }
+            }
+        };
+    }
+
+    key_type_suite!(i32_keys, i32, any::<i32>());
+    key_type_suite!(string_keys, String, string_strategy());
+    key_type_suite!(tuple_keys, (i16, u8), (any::<i16>(), 0u8..4));
+    key_type_suite!(custom_id_keys, UserId, (0u32..300).prop_map(UserId));
+}
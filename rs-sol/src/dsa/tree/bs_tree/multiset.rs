@@ -0,0 +1,201 @@
+/*
+multiset (bag): like the tree, but a key can be in it more than once.
+STRUCTURE:
+    * a BstMap from key to count, so every distinct key is still one node and the tree rules
+      (no duplicate keys, left < node < right) hold for the keys. A node is never stored with
+      count 0, the key is removed instead.
+    * len is the number of occurrences (sum of the counts), kept next to the map so get_len is
+      O(1). distinct_len is the number of nodes.
+OPERATIONS:
+    - insert: new key -> new node with count 1, existing key -> count + 1
+    - delete: count - 1, the node is removed when the count reaches 0
+    - delete_all: removes the node with all its occurrences
+    - count: 0 for a key that is not in the multiset
+*/
+
+use super::map::BstMap;
+
+#[derive(Debug)]
+pub struct BstMultiset<K> {
+    counts: BstMap<K, usize>,
+    len: usize,
+}
+
+impl<K> Default for BstMultiset<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> BstMultiset<K> {
+    pub fn new() -> Self {
+        Self {
+            counts: BstMap::new(),
+            len: 0,
+        }
+    }
+
+    // occurrences, a key inserted 3 times counts 3
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub fn distinct_len(&self) -> usize {
+        self.counts.get_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<K: Ord> BstMultiset<K> {
+    // returns the count of key after the insertion
+    pub fn insert(
+        &mut self,
+        key: K,
+    ) -> usize {
+        self.len += 1;
+
+        let mut count = self.counts.entry(key).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    // removes one occurrence, false if the key is not in the multiset
+    pub fn delete(
+        &mut self,
+        key: &K,
+    ) -> bool {
+        let Some(mut count) = self.counts.get_mut(key) else {
+            return false;
+        };
+        *count -= 1;
+        let is_last = *count == 0;
+        drop(count);
+
+        if is_last {
+            self.counts.remove(key);
+        }
+        self.len -= 1;
+
+        true
+    }
+
+    // removes every occurrence, returns how many there were
+    pub fn delete_all(
+        &mut self,
+        key: &K,
+    ) -> usize {
+        let removed = self.counts.remove(key).unwrap_or(0);
+        self.len -= removed;
+
+        removed
+    }
+
+    pub fn count(
+        &self,
+        key: &K,
+    ) -> usize {
+        self.counts.get(key).map_or(0, |count| *count)
+    }
+
+    pub fn contains(
+        &self,
+        key: &K,
+    ) -> bool {
+        self.counts.contains_key(key)
+    }
+}
+
+impl<K: Ord> FromIterator<K> for BstMultiset<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut multiset = Self::new();
+        for key in keys {
+            multiset.insert(key);
+        }
+
+        multiset
+    }
+}
+
+#[cfg(test)]
+mod multiset_tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn counts_match_btreemap() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy =
+            proptest::collection::vec((0u8..5, -20i32..20), 0..300);
+
+        runner
+            .run(&strategy, |operations| {
+                let mut multiset = BstMultiset::new();
+                let mut expected: BTreeMap<i32, usize> = BTreeMap::new();
+
+                for (operation, key) in operations {
+                    match operation {
+                        0 | 1 => {
+                            let count = expected.entry(key).or_insert(0);
+                            *count += 1;
+                            prop_assert_eq!(multiset.insert(key), *count);
+                        }
+                        2 | 3 => {
+                            let was_in = expected.contains_key(&key);
+                            if was_in {
+                                *expected.get_mut(&key).unwrap() -= 1;
+                                expected.retain(|_, count| *count > 0);
+                            }
+                            prop_assert_eq!(multiset.delete(&key), was_in);
+                        }
+                        _ => {
+                            let removed =
+                                expected.remove(&key).unwrap_or(0);
+                            prop_assert_eq!(
+                                multiset.delete_all(&key),
+                                removed
+                            );
+                        }
+                    }
+
+                    let expected_count =
+                        expected.get(&key).copied().unwrap_or(0);
+                    let expected_len: usize = expected.values().sum();
+                    prop_assert_eq!(multiset.count(&key), expected_count);
+                    prop_assert_eq!(multiset.get_len(), expected_len);
+                    prop_assert_eq!(
+                        multiset.distinct_len(),
+                        expected.len()
+                    );
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn duplicates_are_counted() {
+        let mut words: BstMultiset<&str> =
+            ["to", "be", "or", "not", "to", "be"].into_iter().collect();
+        assert_eq!(words.get_len(), 6);
+        assert_eq!(words.distinct_len(), 4);
+        assert_eq!(words.count(&"to"), 2);
+        assert_eq!(words.count(&"is"), 0);
+
+        assert!(words.delete(&"be"));
+        assert!(words.contains(&"be"));
+        assert!(words.delete(&"be"));
+        assert!(!words.contains(&"be"));
+        assert!(!words.delete(&"be"));
+
+        assert_eq!(words.delete_all(&"to"), 2);
+        assert_eq!(words.delete_all(&"to"), 0);
+        assert_eq!((words.get_len(), words.distinct_len()), (2, 2));
+        assert!(!words.is_empty());
+    }
+}
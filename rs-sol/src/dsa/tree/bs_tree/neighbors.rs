@@ -0,0 +1,197 @@
+/*
+neighbor queries: the closest keys around a given key. The key does not have to be in the tree.
+    * successor(k):   smallest key >  k
+    * ceiling(k):     smallest key >= k
+    * predecessor(k): biggest key  <  k
+    * floor(k):       biggest key  <= k
+    all four are one descent from the root (first_node_from / last_node_to of range.rs with an
+    excluded or included bound), O(height).
+    * min / max: leftmost / rightmost node
+    * pop_min / pop_max: the min node has no left child (max node no right child), so removing
+      it is delete_node's case 0 or 1, no data swapping.
+*/
+
+use std::ops::Bound;
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    BinarySearchTree<K, A, C>
+{
+    pub fn successor(
+        &self,
+        key: &K,
+    ) -> Option<K> {
+        Self::key_of(self.first_node_from(Bound::Excluded(key)))
+    }
+
+    pub fn ceiling(
+        &self,
+        key: &K,
+    ) -> Option<K> {
+        Self::key_of(self.first_node_from(Bound::Included(key)))
+    }
+
+    pub fn predecessor(
+        &self,
+        key: &K,
+    ) -> Option<K> {
+        Self::key_of(self.last_node_to(Bound::Excluded(key)))
+    }
+
+    pub fn floor(
+        &self,
+        key: &K,
+    ) -> Option<K> {
+        Self::key_of(self.last_node_to(Bound::Included(key)))
+    }
+
+    pub fn min(&self) -> Option<K> {
+        Self::key_of(self.min_node())
+    }
+
+    pub fn max(&self) -> Option<K> {
+        Self::key_of(self.max_node())
+    }
+
+    pub fn pop_min(&mut self) -> Option<K> {
+        let min_rc = self.min_node()?;
+        Some(self.pop_node(min_rc))
+    }
+
+    pub fn pop_max(&mut self) -> Option<K> {
+        let max_rc = self.max_node()?;
+        Some(self.pop_node(max_rc))
+    }
+
+    fn min_node(&self) -> Option<NodeRc<K, A>> {
+        self.root.as_ref().map(BinarySearchTreeNode::min_of_subtree)
+    }
+
+    fn max_node(&self) -> Option<NodeRc<K, A>> {
+        self.root.as_ref().map(BinarySearchTreeNode::max_of_subtree)
+    }
+
+    // removes a node with at most 1 child, delete_node unlinks that node itself
+    fn pop_node(
+        &mut self,
+        node_rc: NodeRc<K, A>,
+    ) -> K {
+        let removed_rc = self.delete_node(&node_rc);
+        drop(node_rc);
+
+        BinarySearchTreeNode::take_data(removed_rc)
+    }
+
+    fn key_of(node: Option<NodeRc<K, A>>) -> Option<K> {
+        node.map(|node_rc| node_rc.borrow().data.clone())
+    }
+}
+
+#[cfg(test)]
+mod neighbors_tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn neighbors_match_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..200),
+            -110i32..110,
+        );
+
+        runner
+            .run(&strategy, |(values, probe)| {
+                let mut bstree = BinarySearchTree::new();
+                for &value in &values {
+                    bstree.insert(value);
+                }
+                let expected: BTreeSet<i32> = values.into_iter().collect();
+
+                prop_assert_eq!(
+                    bstree.successor(&probe),
+                    expected.range(probe + 1..).next().copied()
+                );
+                prop_assert_eq!(
+                    bstree.ceiling(&probe),
+                    expected.range(probe..).next().copied()
+                );
+                prop_assert_eq!(
+                    bstree.predecessor(&probe),
+                    expected.range(..probe).next_back().copied()
+                );
+                prop_assert_eq!(
+                    bstree.floor(&probe),
+                    expected.range(..=probe).next_back().copied()
+                );
+                prop_assert_eq!(bstree.min(), expected.first().copied());
+                prop_assert_eq!(bstree.max(), expected.last().copied());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn pop_min_and_pop_max_drain_the_tree_in_order() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..200),
+            proptest::collection::vec(any::<bool>(), 0..250),
+        );
+
+        runner
+            .run(&strategy, |(values, pop_min)| {
+                let mut bstree = BinarySearchTree::new();
+                for &value in &values {
+                    bstree.insert(value);
+                }
+                let mut expected: BTreeSet<i32> =
+                    values.into_iter().collect();
+
+                for from_min in pop_min {
+                    if from_min {
+                        prop_assert_eq!(
+                            bstree.pop_min(),
+                            expected.pop_first()
+                        );
+                    } else {
+                        prop_assert_eq!(
+                            bstree.pop_max(),
+                            expected.pop_last()
+                        );
+                    }
+                    prop_assert_eq!(bstree.get_len(), expected.len());
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn neighbors_of_missing_keys() {
+        let mut bstree = BinarySearchTree::new();
+        for word in ["delta", "alpha", "foxtrot", "charlie"] {
+            bstree.insert(word.to_string());
+        }
+
+        let echo = "echo".to_string();
+        assert_eq!(bstree.floor(&echo).as_deref(), Some("delta"));
+        assert_eq!(bstree.ceiling(&echo).as_deref(), Some("foxtrot"));
+        assert_eq!(bstree.successor(&"foxtrot".to_string()), None);
+        assert_eq!(bstree.predecessor(&"alpha".to_string()), None);
+        assert_eq!(bstree.pop_min().as_deref(), Some("alpha"));
+        assert_eq!(bstree.min().as_deref(), Some("charlie"));
+
+        let mut empty: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert_eq!(empty.floor(&1), None);
+        assert_eq!(empty.pop_max(), None);
+    }
+}
@@ -0,0 +1,233 @@
+/*
+read-only node handles: the node type keeps its fields private (a changed key or link would
+break the ordering, sizes or parent pointers), so the Rc handed out by search / get_root is
+opaque outside this module. NodeRef is a view of one node that can only read.
+    * key, left, right, parent, is_leaf, num_of_childs, size (nodes in its subtree)
+    * it borrows the tree ('a), so the tree can not change while handles are alive, and a
+      handle never points to a node that was removed in the meantime.
+    * two handles are equal when they are the same node (pointer equality), not when the keys
+      are equal. That is what LCA-like algorithms need to compare positions.
+    * key clones the key out (K: Clone), like Iter and Cursor.
+root_ref / node_ref(k) give the first handle, a Cursor gives the handle of its position.
+*/
+
+use std::{fmt, marker::PhantomData, rc::Rc};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+pub struct NodeRef<'a, K, A = ()> {
+    node_rc: NodeRc<K, A>,
+    _tree: PhantomData<&'a BinarySearchTreeNode<K, A>>,
+}
+
+impl<K, A> NodeRef<'_, K, A> {
+    pub(super) fn new(node_rc: NodeRc<K, A>) -> Self {
+        Self {
+            node_rc,
+            _tree: PhantomData,
+        }
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn root_ref(&self) -> Option<NodeRef<'_, K, A>> {
+        self.root.clone().map(NodeRef::new)
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    pub fn node_ref(
+        &self,
+        key: &K,
+    ) -> Option<NodeRef<'_, K, A>> {
+        self.search(key).map(NodeRef::new)
+    }
+}
+
+impl<'a, K, A: Augmentation<K>> NodeRef<'a, K, A> {
+    pub fn left(&self) -> Option<NodeRef<'a, K, A>> {
+        self.node_rc.borrow().left_child.clone().map(NodeRef::new)
+    }
+
+    pub fn right(&self) -> Option<NodeRef<'a, K, A>> {
+        self.node_rc.borrow().right_child.clone().map(NodeRef::new)
+    }
+
+    // None for the root
+    pub fn parent(&self) -> Option<NodeRef<'a, K, A>> {
+        self.node_rc.borrow().parent_rc().map(NodeRef::new)
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.num_of_childs() == 0
+    }
+
+    pub fn num_of_childs(&self) -> usize {
+        self.node_rc.borrow().num_of_childs()
+    }
+
+    // nodes in the subtree of this node, itself included
+    pub fn size(&self) -> usize {
+        self.node_rc.borrow().size
+    }
+}
+
+impl<K: Clone, A> NodeRef<'_, K, A> {
+    pub fn key(&self) -> K {
+        self.node_rc.borrow().data.clone()
+    }
+}
+
+impl<K, A> Clone for NodeRef<'_, K, A> {
+    fn clone(&self) -> Self {
+        Self::new(self.node_rc.clone())
+    }
+}
+
+impl<K, A> PartialEq for NodeRef<'_, K, A> {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        Rc::ptr_eq(&self.node_rc, &other.node_rc)
+    }
+}
+
+impl<K, A> Eq for NodeRef<'_, K, A> {}
+
+impl<K: fmt::Debug, A> fmt::Debug for NodeRef<'_, K, A> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("NodeRef")
+            .field("key", &self.node_rc.borrow().data)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod node_ref_tests {
+    use super::super::test_utils::tree_of;
+    use super::*;
+    use proptest::prelude::*;
+
+    // lowest common ancestor from the outside: the ancestors of one node, then the first
+    // ancestor of the other node that is among them
+    fn lca<'a>(
+        first: NodeRef<'a, i32>,
+        second: NodeRef<'a, i32>,
+    ) -> NodeRef<'a, i32> {
+        let mut ancestors = vec![first.clone()];
+        while let Some(parent) = ancestors.last().unwrap().parent() {
+            ancestors.push(parent);
+        }
+
+        let mut current = second;
+        while !ancestors.contains(&current) {
+            current = current.parent().expect("both nodes are in one tree");
+        }
+
+        current
+    }
+
+    // keys on the path from the root down to key, walking only with the handles
+    fn path_to(
+        bstree: &BinarySearchTree<i32>,
+        key: i32,
+    ) -> Vec<i32> {
+        let mut path = Vec::new();
+        let mut current = bstree.root_ref();
+        while let Some(node) = current {
+            path.push(node.key());
+            current = match key.cmp(&node.key()) {
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Less => node.left(),
+                std::cmp::Ordering::Greater => node.right(),
+            };
+        }
+
+        path
+    }
+
+    #[test]
+    fn walking_the_real_tree() {
+        //       50
+        //    30    70
+        //  20  40    80
+        let bstree = tree_of(&[50, 30, 70, 20, 40, 80]);
+        let root = bstree.root_ref().unwrap();
+        assert_eq!(root.key(), 50);
+        assert_eq!((root.size(), root.num_of_childs()), (6, 2));
+        assert_eq!(root.parent(), None);
+
+        let seventy = root.right().unwrap();
+        assert_eq!(seventy.left(), None);
+        assert_eq!(seventy.right().map(|node| node.key()), Some(80));
+        assert!(!seventy.is_leaf() && seventy.num_of_childs() == 1);
+
+        let forty = bstree.node_ref(&40).unwrap();
+        assert!(forty.is_leaf());
+        assert_eq!(forty.parent().unwrap().parent(), Some(root.clone()));
+        assert_eq!(bstree.node_ref(&45), None);
+
+        assert_eq!(
+            lca(forty.clone(), bstree.node_ref(&20).unwrap()).key(),
+            30
+        );
+        assert_eq!(lca(forty.clone(), bstree.node_ref(&80).unwrap()), root);
+        assert_eq!(lca(forty.clone(), forty.clone()), forty);
+
+        // same key, same node
+        let mut cursor = bstree.cursor_front();
+        cursor.move_next();
+        assert_eq!(cursor.node(), bstree.node_ref(&30));
+        assert_eq!(path_to(&bstree, 40), [50, 30, 40]);
+    }
+
+    #[test]
+    fn handles_agree_with_the_tree() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-100i32..100, 1..100);
+
+        runner
+            .run(&strategy, |values| {
+                let bstree = tree_of(&values);
+
+                for value in &values {
+                    let path = path_to(&bstree, *value);
+                    prop_assert_eq!(
+                        path.len() - 1,
+                        bstree.depth_of(value).unwrap()
+                    );
+
+                    let node = bstree.node_ref(value).unwrap();
+                    let left_size =
+                        node.left().map_or(0, |left| left.size());
+                    let right_size =
+                        node.right().map_or(0, |right| right.size());
+                    prop_assert_eq!(
+                        node.size(),
+                        left_size + right_size + 1
+                    );
+                    prop_assert_eq!(node.is_leaf(), node.size() == 1);
+
+                    // the node is a child of its parent, on the side its key says
+                    if let Some(parent) = node.parent() {
+                        let side = if *value < parent.key() {
+                            parent.left()
+                        } else {
+                            parent.right()
+                        };
+                        prop_assert_eq!(side, Some(node.clone()));
+                    }
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
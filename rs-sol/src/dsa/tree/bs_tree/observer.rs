@@ -0,0 +1,313 @@
+/*
+operation trace: insert_observed, delete_observed and search_observed do the same work as
+insert, delete and search, but report every step to a TreeObserver.
+    * events borrow the keys from the tree (TreeEvent<&K>), nothing is cloned unless the
+      observer wants to keep them.
+    * the order of events follows the code: a Compared for every node on the path, then the
+      direction (WentLeft / WentRight) or Found. A walk that falls off the tree ends with
+      InsertedLeaf (insert) or NotFound (search, delete).
+    * delete: the search events, then DeletionCase with the number of childs of the target
+      (0, 1 or 2, see the header of mod.rs). For 2 childs the predecessor (max of the left
+      subtree) key goes up into the target (CopiedPredecessor) and the predecessor node is
+      removed, which is a second DeletionCase with 0 or 1 childs. With a successor deletion
+      strategy (deletion.rs) it is CopiedSuccessor and the min of the right subtree.
+    * () ignores every event, the plain insert/delete use it.
+EventRecorder keeps the whole log (cloned keys), to replay an operation step by step or to
+count the comparisons of a call.
+*/
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEvent<K> {
+    // the searched/inserted key was compared against the node holding key
+    Compared { key: K },
+    WentLeft,
+    WentRight,
+    // the key is in the tree (insert: the duplicate is ignored)
+    Found { key: K },
+    NotFound,
+    // a new leaf is linked below parent (None: the new node is the root)
+    InsertedLeaf { key: K, parent: Option<K> },
+    DeletionCase { key: K, num_of_childs: usize },
+    // the target of a deletion with 2 childs now holds its predecessor's key
+    CopiedPredecessor { key: K },
+    CopiedSuccessor { key: K },
+}
+
+pub trait TreeObserver<K> {
+    fn on_event(
+        &mut self,
+        event: TreeEvent<&K>,
+    );
+}
+
+impl<K> TreeObserver<K> for () {
+    fn on_event(
+        &mut self,
+        _event: TreeEvent<&K>,
+    ) {
+    }
+}
+
+impl<K: Clone> TreeEvent<&K> {
+    pub fn cloned(&self) -> TreeEvent<K> {
+        match *self {
+            TreeEvent::Compared { key } => {
+                TreeEvent::Compared { key: key.clone() }
+            }
+            TreeEvent::WentLeft => TreeEvent::WentLeft,
+            TreeEvent::WentRight => TreeEvent::WentRight,
+            TreeEvent::Found { key } => {
+                TreeEvent::Found { key: key.clone() }
+            }
+            TreeEvent::NotFound => TreeEvent::NotFound,
+            TreeEvent::InsertedLeaf { key, parent } => {
+                TreeEvent::InsertedLeaf {
+                    key: key.clone(),
+                    parent: parent.cloned(),
+                }
+            }
+            TreeEvent::DeletionCase { key, num_of_childs } => {
+                TreeEvent::DeletionCase {
+                    key: key.clone(),
+                    num_of_childs,
+                }
+            }
+            TreeEvent::CopiedPredecessor { key } => {
+                TreeEvent::CopiedPredecessor { key: key.clone() }
+            }
+            TreeEvent::CopiedSuccessor { key } => {
+                TreeEvent::CopiedSuccessor { key: key.clone() }
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug> fmt::Display for TreeEvent<K> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            TreeEvent::Compared { key } => {
+                write!(f, "compare with {:?}", key)
+            }
+            TreeEvent::WentLeft => write!(f, "go left"),
+            TreeEvent::WentRight => write!(f, "go right"),
+            TreeEvent::Found { key } => write!(f, "found {:?}", key),
+            TreeEvent::NotFound => write!(f, "not found"),
+            TreeEvent::InsertedLeaf { key, parent: None } => {
+                write!(f, "insert {:?} as the root", key)
+            }
+            TreeEvent::InsertedLeaf {
+                key,
+                parent: Some(parent),
+            } => write!(f, "insert {:?} below {:?}", key, parent),
+            TreeEvent::DeletionCase { key, num_of_childs } => {
+                write!(f, "delete {:?} with {} childs", key, num_of_childs)
+            }
+            TreeEvent::CopiedPredecessor { key } => {
+                write!(f, "copy predecessor {:?} up", key)
+            }
+            TreeEvent::CopiedSuccessor { key } => {
+                write!(f, "copy successor {:?} up", key)
+            }
+        }
+    }
+}
+
+// collects every event it gets, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRecorder<K> {
+    events: Vec<TreeEvent<K>>,
+}
+
+impl<K> Default for EventRecorder<K> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<K> EventRecorder<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> &[TreeEvent<K>] {
+        &self.events
+    }
+
+    // gives the log so far and starts a new one, handy to split the log per call
+    pub fn take_events(&mut self) -> Vec<TreeEvent<K>> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn comparisons(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, TreeEvent::Compared { .. }))
+            .count()
+    }
+}
+
+impl<K: Clone> TreeObserver<K> for EventRecorder<K> {
+    fn on_event(
+        &mut self,
+        event: TreeEvent<&K>,
+    ) {
+        self.events.push(event.cloned());
+    }
+}
+
+#[cfg(test)]
+mod observer_tests {
+    use super::super::{BinarySearchTree, test_utils::sample_tree};
+    use super::*;
+    use proptest::prelude::*;
+
+    use TreeEvent::*;
+
+    #[test]
+    fn insert_and_search_traces() {
+        let mut bstree = sample_tree();
+        let mut recorder = EventRecorder::new();
+
+        bstree.insert_observed(45, &mut recorder);
+        assert_eq!(
+            recorder.take_events(),
+            vec![
+                Compared { key: 50 },
+                WentLeft,
+                Compared { key: 30 },
+                WentRight,
+                Compared { key: 40 },
+                WentRight,
+                InsertedLeaf {
+                    key: 45,
+                    parent: Some(40),
+                },
+            ]
+        );
+
+        bstree.insert_observed(30, &mut recorder);
+        assert_eq!(
+            recorder.take_events(),
+            vec![
+                Compared { key: 50 },
+                WentLeft,
+                Compared { key: 30 },
+                Found { key: 30 },
+            ]
+        );
+
+        assert!(bstree.search_observed(&75, &mut recorder).is_none());
+        let steps: Vec<String> = recorder
+            .events()
+            .iter()
+            .map(|event| event.to_string())
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                "compare with 50",
+                "go right",
+                "compare with 70",
+                "go right",
+                "compare with 80",
+                "go left",
+                "not found",
+            ]
+        );
+
+        let mut empty = BinarySearchTree::new();
+        empty.insert_observed(1, &mut recorder);
+        assert_eq!(
+            recorder.events().last(),
+            Some(&InsertedLeaf {
+                key: 1,
+                parent: None
+            })
+        );
+    }
+
+    #[test]
+    fn deletion_cases_are_reported() {
+        let mut bstree = sample_tree();
+        let mut recorder = EventRecorder::new();
+
+        // 30 has 2 childs: its predecessor 20 goes up, the node of 20 is a leaf
+        bstree.delete_observed(&30, &mut recorder);
+        let events = recorder.take_events();
+        assert_eq!(
+            events[events.len() - 3..],
+            [
+                DeletionCase {
+                    key: 30,
+                    num_of_childs: 2,
+                },
+                CopiedPredecessor { key: 20 },
+                DeletionCase {
+                    key: 30,
+                    num_of_childs: 0,
+                },
+            ]
+        );
+
+        bstree.delete_observed(&60, &mut recorder);
+        assert_eq!(
+            recorder.events().last(),
+            Some(&DeletionCase {
+                key: 60,
+                num_of_childs: 1,
+            })
+        );
+
+        recorder.take_events();
+        bstree.delete_observed(&99, &mut recorder);
+        assert_eq!(recorder.events().last(), Some(&NotFound));
+        assert_eq!(
+            bstree.iter().collect::<Vec<_>>(),
+            vec![20, 40, 50, 65, 70, 80]
+        );
+    }
+
+    #[test]
+    fn comparisons_are_depth_plus_one() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-100i32..100, 1..100);
+
+        runner
+            .run(&strategy, |values| {
+                let mut bstree = BinarySearchTree::new();
+                let mut recorder = EventRecorder::new();
+                for &value in &values {
+                    bstree.insert_observed(value, &mut recorder);
+                }
+
+                // an existing key is compared with every node from the root down to it
+                for value in &values {
+                    recorder.take_events();
+                    let found_rc =
+                        bstree.search_observed(value, &mut recorder);
+                    prop_assert!(found_rc.is_some());
+
+                    let mut depth = 0;
+                    let mut current_rc = found_rc.unwrap();
+                    loop {
+                        let maybe_parent_rc =
+                            current_rc.borrow().parent_rc();
+                        let Some(parent_rc) = maybe_parent_rc else {
+                            break;
+                        };
+                        depth += 1;
+                        current_rc = parent_rc;
+                    }
+                    prop_assert_eq!(recorder.comparisons(), depth + 1);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+/*
+order statistics: every node knows the size of its subtree (itself + all nodes below it).
+    * insert and delete_node keep the sizes right, only the nodes on the path from the
+      linked/unlinked node up to the root change. update_upward fixes them bottom up,
+      together with the augmentation summaries (augment.rs).
+    * select(k): k-th smallest key (0 based). The left subtree of a node holds exactly
+      left_size keys smaller than it, so at every node we know if the k-th key is on the left,
+      is the node itself or is on the right (skipping left_size + 1 keys). O(height)
+    * rank(k): number of keys smaller than k, k does not have to be in the tree. Every node we
+      go right from is smaller than k, together with its left subtree. O(height)
+    * median: middle key, the lower one of the two middle keys if len is even.
+*/
+
+use std::{cmp::Ordering, ops::Bound};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+};
+
+impl<K: Clone, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn select(
+        &self,
+        index: usize,
+    ) -> Option<K> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut index = index;
+        let mut current_node_ptr = self.root.clone();
+
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            let left_size = BinarySearchTreeNode::subtree_size(
+                node.left_child.as_ref(),
+            );
+
+            current_node_ptr = match index.cmp(&left_size) {
+                Ordering::Less => node.left_child.clone(),
+                Ordering::Equal => return Some(node.data.clone()),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    node.right_child.clone()
+                }
+            };
+        }
+
+        unreachable!("index is smaller than len, the key must be found");
+    }
+
+    pub fn median(&self) -> Option<K> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.select((self.len - 1) / 2)
+    }
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    pub fn rank(
+        &self,
+        key: &K,
+    ) -> usize {
+        self.count_until(Bound::Excluded(key))
+    }
+}
+
+#[cfg(test)]
+mod order_statistic_tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn sizes_stay_right_after_inserts_and_deletes() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy =
+            proptest::collection::vec((any::<bool>(), -50i32..50), 0..300);
+
+        runner
+            .run(&strategy, |operations| {
+                let mut bstree = BinarySearchTree::new();
+                for (is_insert, value) in operations {
+                    if is_insert {
+                        bstree.insert(value);
+                    } else {
+                        bstree.delete(&value);
+                    }
+                    prop_assert_eq!(bstree.validate(), Ok(()));
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn select_and_rank_match_sorted_keys() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..200),
+            proptest::collection::vec(-100i32..100, 0..50),
+            -110i32..110,
+        );
+
+        runner
+            .run(&strategy, |(values, deleted, probe)| {
+                let mut bstree = BinarySearchTree::new();
+                for &value in &values {
+                    bstree.insert(value);
+                }
+                for value in &deleted {
+                    bstree.delete(value);
+                }
+
+                let mut expected: BTreeSet<i32> =
+                    values.into_iter().collect();
+                for value in &deleted {
+                    expected.remove(value);
+                }
+                let sorted: Vec<i32> = expected.iter().copied().collect();
+
+                for (index, &key) in sorted.iter().enumerate() {
+                    prop_assert_eq!(bstree.select(index), Some(key));
+                    prop_assert_eq!(bstree.rank(&key), index);
+                }
+                prop_assert_eq!(bstree.select(sorted.len()), None);
+                prop_assert_eq!(
+                    bstree.rank(&probe),
+                    expected.range(..probe).count()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn median_of_odd_and_even_trees() {
+        let mut bstree = BinarySearchTree::new();
+        assert_eq!(bstree.median(), None);
+
+        for value in [50, 30, 70, 20, 40] {
+            bstree.insert(value);
+        }
+        assert_eq!(bstree.median(), Some(40));
+
+        bstree.insert(80);
+        assert_eq!(bstree.median(), Some(40));
+
+        // two-child delete of the root moves 40 up, sizes must follow
+        bstree.delete(&50);
+        assert_eq!(bstree.median(), Some(40));
+        assert_eq!(bstree.select(3), Some(70));
+        assert_eq!(bstree.rank(&75), 4);
+    }
+}
@@ -0,0 +1,412 @@
+/*
+range queries: keys between two bounds, in sorted order.
+    * the first node of the range is found by descending from the root: a node that satisfies the
+      lower bound is a candidate and we continue left to look for a smaller one, otherwise the
+      whole left subtree is too small as well and we continue right. O(height), subtrees outside
+      of the range are never entered. The last node is found the same way from the other side.
+    * from the first node, Range walks with next_in_order until it reaches the last node, so only
+      keys inside the range are visited. Both ends can be consumed (DoubleEndedIterator).
+    * count_range does not walk the keys, it uses the subtree sizes (see order_statistic.rs).
+    * bounds can be anything RangeBounds (a..b, a..=b, ..b, a.., .., (Bound, Bound)).
+      An inverted range (start after end) is empty, it does not panic.
+*/
+
+use std::{
+    cmp::Ordering,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    rc::Rc,
+};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+pub struct Range<'a, K, A = ()> {
+    front: Option<NodeRc<K, A>>,
+    back: Option<NodeRc<K, A>>,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // true if key is not below the lower bound (in the order of the comparator)
+    pub(super) fn satisfies_lower(
+        &self,
+        key: &K,
+        lower: Bound<&K>,
+    ) -> bool {
+        match lower {
+            Bound::Included(start) => {
+                self.comparator.compare(key, start) != Ordering::Less
+            }
+            Bound::Excluded(start) => {
+                self.comparator.compare(key, start) == Ordering::Greater
+            }
+            Bound::Unbounded => true,
+        }
+    }
+
+    // true if key is not above the upper bound
+    pub(super) fn satisfies_upper(
+        &self,
+        key: &K,
+        upper: Bound<&K>,
+    ) -> bool {
+        match upper {
+            Bound::Included(end) => {
+                self.comparator.compare(key, end) != Ordering::Greater
+            }
+            Bound::Excluded(end) => {
+                self.comparator.compare(key, end) == Ordering::Less
+            }
+            Bound::Unbounded => true,
+        }
+    }
+
+    // smallest node that satisfies the lower bound
+    pub(super) fn first_node_from(
+        &self,
+        lower: Bound<&K>,
+    ) -> Option<NodeRc<K, A>> {
+        let mut candidate = None;
+        let mut current_node_ptr = self.root.clone();
+
+        while let Some(node_rc) = current_node_ptr {
+            let in_bound;
+            current_node_ptr = {
+                let node = node_rc.borrow();
+                in_bound = self.satisfies_lower(&node.data, lower);
+                if in_bound {
+                    node.left_child.clone()
+                } else {
+                    node.right_child.clone()
+                }
+            };
+
+            if in_bound {
+                candidate = Some(node_rc);
+            }
+        }
+
+        candidate
+    }
+
+    // biggest node that satisfies the upper bound
+    pub(super) fn last_node_to(
+        &self,
+        upper: Bound<&K>,
+    ) -> Option<NodeRc<K, A>> {
+        let mut candidate = None;
+        let mut current_node_ptr = self.root.clone();
+
+        while let Some(node_rc) = current_node_ptr {
+            let in_bound;
+            current_node_ptr = {
+                let node = node_rc.borrow();
+                in_bound = self.satisfies_upper(&node.data, upper);
+                if in_bound {
+                    node.right_child.clone()
+                } else {
+                    node.left_child.clone()
+                }
+            };
+
+            if in_bound {
+                candidate = Some(node_rc);
+            }
+        }
+
+        candidate
+    }
+
+    // first and last node of the range, None if there is no key in the range
+    fn range_ends(
+        &self,
+        bounds: &impl RangeBounds<K>,
+    ) -> Option<(NodeRc<K, A>, NodeRc<K, A>)> {
+        let first_rc = self.first_node_from(bounds.start_bound())?;
+        let last_rc = self.last_node_to(bounds.end_bound())?;
+
+        let ordering = self
+            .comparator
+            .compare(&first_rc.borrow().data, &last_rc.borrow().data);
+        if ordering == Ordering::Greater {
+            // both ends exist but they passed each other, nothing in between
+            return None;
+        }
+
+        Some((first_rc, last_rc))
+    }
+
+    pub fn range(
+        &self,
+        bounds: impl RangeBounds<K>,
+    ) -> Range<'_, K, A> {
+        let (front, back) = match self.range_ends(&bounds) {
+            Some((first_rc, last_rc)) => (Some(first_rc), Some(last_rc)),
+            None => (None, None),
+        };
+
+        Range {
+            front,
+            back,
+            _tree: PhantomData,
+        }
+    }
+
+    // number of keys that satisfy the upper bound. Same descent as last_node_to, but every
+    // node that satisfies the bound adds itself and its whole left subtree (subtree sizes).
+    pub(super) fn count_until(
+        &self,
+        upper: Bound<&K>,
+    ) -> usize {
+        let mut count = 0;
+        let mut current_node_ptr = self.root.clone();
+
+        while let Some(node_rc) = current_node_ptr {
+            let node = node_rc.borrow();
+            current_node_ptr = if self.satisfies_upper(&node.data, upper) {
+                count += 1 + BinarySearchTreeNode::subtree_size(
+                    node.left_child.as_ref(),
+                );
+                node.right_child.clone()
+            } else {
+                node.left_child.clone()
+            };
+        }
+
+        count
+    }
+
+    // number of keys in the range, O(height). The keys below the lower bound are the keys
+    // that satisfy the opposite upper bound, they are subtracted from the keys up to the end.
+    pub fn count_range(
+        &self,
+        bounds: impl RangeBounds<K>,
+    ) -> usize {
+        let below_start = match bounds.start_bound() {
+            Bound::Included(start) => {
+                self.count_until(Bound::Excluded(start))
+            }
+            Bound::Excluded(start) => {
+                self.count_until(Bound::Included(start))
+            }
+            Bound::Unbounded => 0,
+        };
+
+        // an inverted range has less keys up to its end than below its start
+        self.count_until(bounds.end_bound())
+            .saturating_sub(below_start)
+    }
+
+    // removes every key in the range, returns how many keys are removed
+    pub fn remove_range(
+        &mut self,
+        bounds: impl RangeBounds<K>,
+    ) -> usize {
+        let Some((first_rc, last_rc)) = self.range_ends(&bounds) else {
+            return 0;
+        };
+
+        // the keys in the range are removed from small to big. Deleting a node with 2 childs
+        // moves its max_lsub key into it, that key is below the range (everything in the range
+        // before it is already removed). So the successor node found before deleting still
+        // holds the next key of the range.
+        // with the successor deletion strategy (deletion.rs) it is the other way around: the
+        // next key moves into the deleted node and the node of the next key is unlinked, so
+        // the walk continues from the same node.
+        let mut removed = 0;
+        let (mut current_rc, mut last_rc) = (first_rc, last_rc);
+        loop {
+            let is_last = Rc::ptr_eq(&current_rc, &last_rc);
+            let mut next_rc =
+                BinarySearchTreeNode::next_in_order(&current_rc);
+
+            let removed_rc = self.delete_node(&current_rc);
+            removed += 1;
+
+            if next_rc
+                .as_ref()
+                .is_some_and(|next_rc| Rc::ptr_eq(next_rc, &removed_rc))
+            {
+                if Rc::ptr_eq(&removed_rc, &last_rc) {
+                    last_rc = current_rc.clone();
+                }
+                next_rc = Some(current_rc);
+            }
+
+            match next_rc {
+                Some(next_rc) if !is_last => current_rc = next_rc,
+                _ => break,
+            }
+        }
+
+        removed
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for Range<'_, K, A> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let front_rc = self.front.take()?;
+
+        if self
+            .back
+            .as_ref()
+            .is_some_and(|back_rc| Rc::ptr_eq(back_rc, &front_rc))
+        {
+            // front reached back, this is the last key
+            self.back = None;
+        } else {
+            self.front = BinarySearchTreeNode::next_in_order(&front_rc);
+        }
+
+        let key = front_rc.borrow().data.clone();
+        Some(key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> DoubleEndedIterator for Range<'_, K, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let back_rc = self.back.take()?;
+
+        if self
+            .front
+            .as_ref()
+            .is_some_and(|front_rc| Rc::ptr_eq(front_rc, &back_rc))
+        {
+            self.front = None;
+        } else {
+            self.back = BinarySearchTreeNode::prev_in_order(&back_rc);
+        }
+
+        let key = back_rc.borrow().data.clone();
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod range_tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    fn bound_strategy() -> impl Strategy<Value = Bound<i32>> {
+        prop_oneof![
+            (-120i32..120).prop_map(Bound::Included),
+            (-120i32..120).prop_map(Bound::Excluded),
+            Just(Bound::Unbounded),
+        ]
+    }
+
+    // std panics for inverted ranges, the tree gives an empty result instead
+    fn is_inverted(
+        lower: Bound<i32>,
+        upper: Bound<i32>,
+    ) -> bool {
+        match (lower, upper) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => start >= end,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn range_queries_match_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..200),
+            bound_strategy(),
+            bound_strategy(),
+        );
+
+        runner
+            .run(&strategy, |(values, lower, upper)| {
+                let mut bstree = BinarySearchTree::new();
+                for &value in &values {
+                    bstree.insert(value);
+                }
+                let mut expected_set: BTreeSet<i32> =
+                    values.into_iter().collect();
+
+                let expected: Vec<i32> = if is_inverted(lower, upper) {
+                    Vec::new()
+                } else {
+                    expected_set.range((lower, upper)).copied().collect()
+                };
+
+                prop_assert_eq!(
+                    bstree.range((lower, upper)).collect::<Vec<_>>(),
+                    expected.clone()
+                );
+                prop_assert_eq!(
+                    bstree.range((lower, upper)).rev().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>()
+                );
+                prop_assert_eq!(
+                    bstree.count_range((lower, upper)),
+                    expected.len()
+                );
+
+                prop_assert_eq!(
+                    bstree.remove_range((lower, upper)),
+                    expected.len()
+                );
+                expected_set.retain(|key| !expected.contains(key));
+                prop_assert_eq!(bstree.get_len(), expected_set.len());
+                prop_assert_eq!(
+                    bstree.iter().collect::<Vec<_>>(),
+                    expected_set.into_iter().collect::<Vec<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn range_syntax_and_string_keys() {
+        let mut bstree = BinarySearchTree::new();
+        for word in ["kiwi", "apple", "melon", "banana", "cherry", "plum"] {
+            bstree.insert(word.to_string());
+        }
+
+        assert_eq!(
+            bstree
+                .range("b".to_string().."m".to_string())
+                .collect::<Vec<_>>(),
+            vec!["banana", "cherry", "kiwi"]
+        );
+        assert_eq!(bstree.count_range(.."c".to_string()), 2);
+        assert_eq!(bstree.count_range(..), 6);
+
+        let mut numbers = BinarySearchTree::new();
+        for value in [50, 30, 70, 20, 40, 60, 80] {
+            numbers.insert(value);
+        }
+
+        let mut in_range = numbers.range(30..=70);
+        assert_eq!(in_range.next(), Some(30));
+        assert_eq!(in_range.next_back(), Some(70));
+        assert_eq!(in_range.collect::<Vec<_>>(), vec![40, 50, 60]);
+
+        assert_eq!(numbers.range(71..80).next(), None);
+        assert_eq!(
+            numbers
+                .range((Bound::Included(60), Bound::Excluded(40)))
+                .next(),
+            None
+        );
+        assert_eq!(numbers.remove_range(35..=65), 3);
+        assert_eq!(
+            numbers.iter().collect::<Vec<_>>(),
+            vec![20, 30, 70, 80]
+        );
+    }
+}
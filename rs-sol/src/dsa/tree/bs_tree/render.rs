@@ -0,0 +1,479 @@
+/*
+drawing the shape of the tree with box-drawing characters, keys are written with {:?}.
+    * sideways (Display default): the root is on the left, the right subtree above it and the
+      left subtree below it, so reading the keys from bottom to top gives the sorted order.
+      One line per node, works for any tree size.
+              ┌── 80
+          ┌── 70
+          │   └── 60
+          50
+          └── 30
+              └── 20
+      lines come from a reverse in-order walk (right, node, left) with an explicit stack, the
+      prefix of a node is the prefix of its parent plus a vertical bar if the line of the
+      parent is on the other side of the node's subtree.
+    * top-down: every key gets its own column, in in-order position, so keys never overlap
+      and a parent is always between its two childs. Below every level a connector line goes
+      from the left child to the right child over the parent.
+              50
+          ┌───┴───┐
+          30      70
+      the drawing needs a column per key and two lines per level, a tree with more than
+      TOP_DOWN_MAX_CELLS characters in it (after the max_width cut) is drawn sideways instead.
+      Lines are built one level at a time and never wider than max_width.
+    * max_width cuts longer lines (ending with …), compact uses less space around the keys.
+use it as `{}` for the default, or `bstree.render()...` to configure it. Also handy in test
+failure messages: assert!(bstree.validate().is_ok(), "{}", bstree).
+*/
+
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use super::{Augmentation, BinarySearchTree, NaturalOrder, NodeRc};
+
+// top-down drawings bigger than this (characters, before trimming) are drawn sideways
+const TOP_DOWN_MAX_CELLS: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderLayout {
+    Sideways,
+    TopDown,
+}
+
+pub struct TreeRender<'a, K, A = (), C = NaturalOrder> {
+    tree: &'a BinarySearchTree<K, A, C>,
+    layout: RenderLayout,
+    max_width: Option<usize>,
+    compact: bool,
+}
+
+// which side of its parent a node is drawn on in the sideways layout
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Root,
+    Above,
+    Below,
+}
+
+enum SidewaysStep<K, A> {
+    Visit {
+        node_rc: NodeRc<K, A>,
+        prefix: String,
+        side: Side,
+    },
+    Line(String),
+}
+
+impl<K, A, C> BinarySearchTree<K, A, C> {
+    pub fn render(&self) -> TreeRender<'_, K, A, C> {
+        TreeRender {
+            tree: self,
+            layout: RenderLayout::Sideways,
+            max_width: None,
+            compact: false,
+        }
+    }
+}
+
+impl<K, A, C> TreeRender<'_, K, A, C> {
+    pub fn layout(
+        mut self,
+        layout: RenderLayout,
+    ) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    // lines longer than max_width characters are cut
+    pub fn max_width(
+        mut self,
+        max_width: usize,
+    ) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn compact(
+        mut self,
+        compact: bool,
+    ) -> Self {
+        self.compact = compact;
+        self
+    }
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> TreeRender<'_, K, A, C> {
+    fn sideways_lines(&self) -> Vec<String> {
+        // (connector above, connector below, bar, blank)
+        let (above, below, bar, blank) = if self.compact {
+            ("┌ ", "└ ", "│ ", "  ")
+        } else {
+            ("┌── ", "└── ", "│   ", "    ")
+        };
+
+        let mut lines = Vec::with_capacity(self.tree.len);
+        let mut stack: Vec<SidewaysStep<K, A>> = Vec::new();
+        stack.extend(self.tree.root.clone().map(|root_rc| {
+            SidewaysStep::Visit {
+                node_rc: root_rc,
+                prefix: String::new(),
+                side: Side::Root,
+            }
+        }));
+
+        while let Some(step) = stack.pop() {
+            let (node_rc, prefix, side) = match step {
+                SidewaysStep::Line(line) => {
+                    lines.push(line);
+                    continue;
+                }
+                SidewaysStep::Visit {
+                    node_rc,
+                    prefix,
+                    side,
+                } => (node_rc, prefix, side),
+            };
+            let node = node_rc.borrow();
+
+            let connector = match side {
+                Side::Root => "",
+                Side::Above => above,
+                Side::Below => below,
+            };
+            // the right subtree is drawn between the node and its parent if the node is
+            // below its parent, the left subtree if the node is above it
+            let (right_prefix, left_prefix) = match side {
+                Side::Root => (String::new(), String::new()),
+                Side::Above => {
+                    (prefix.clone() + blank, prefix.clone() + bar)
+                }
+                Side::Below => {
+                    (prefix.clone() + bar, prefix.clone() + blank)
+                }
+            };
+
+            // pushed in reverse, the right subtree comes out first
+            stack.extend(node.left_child.clone().map(|left_rc| {
+                SidewaysStep::Visit {
+                    node_rc: left_rc,
+                    prefix: left_prefix,
+                    side: Side::Below,
+                }
+            }));
+            stack.push(SidewaysStep::Line(format!(
+                "{}{}{:?}",
+                prefix, connector, node.data
+            )));
+            stack.extend(node.right_child.clone().map(|right_rc| {
+                SidewaysStep::Visit {
+                    node_rc: right_rc,
+                    prefix: right_prefix,
+                    side: Side::Above,
+                }
+            }));
+        }
+
+        lines
+    }
+
+    fn top_down_lines(&self) -> Vec<String> {
+        let gap = if self.compact { 1 } else { 2 };
+
+        // in-order walk: the column of a key comes from the keys before it
+        let mut nodes: Vec<(NodeRc<K, A>, usize)> =
+            Vec::with_capacity(self.tree.len);
+        let mut stack: Vec<(NodeRc<K, A>, usize)> = Vec::new();
+        let mut current_node_ptr =
+            self.tree.root.clone().map(|root_rc| (root_rc, 0));
+        while current_node_ptr.is_some() || !stack.is_empty() {
+            while let Some((node_rc, depth)) = current_node_ptr {
+                current_node_ptr = node_rc
+                    .borrow()
+                    .left_child
+                    .clone()
+                    .map(|left_rc| (left_rc, depth + 1));
+                stack.push((node_rc, depth));
+            }
+
+            if let Some((node_rc, depth)) = stack.pop() {
+                current_node_ptr = node_rc
+                    .borrow()
+                    .right_child
+                    .clone()
+                    .map(|right_rc| (right_rc, depth + 1));
+                nodes.push((node_rc, depth));
+            }
+        }
+
+        let labels: Vec<Vec<char>> = nodes
+            .iter()
+            .map(|(node_rc, _)| {
+                format!("{:?}", node_rc.borrow().data).chars().collect()
+            })
+            .collect();
+        let mut starts = Vec::with_capacity(labels.len());
+        let mut width = 0;
+        for label in &labels {
+            starts.push(width);
+            width += label.len() + gap;
+        }
+
+        // the lines are cut at max_width anyway, one more character is enough for Display
+        // to see that a line was cut
+        let line_width = self
+            .max_width
+            .map_or(width, |max_width| width.min(max_width + 1));
+        let height =
+            nodes.iter().map(|(_, depth)| depth + 1).max().unwrap_or(0);
+        // a key line and a connector line for every level
+        if line_width.saturating_mul(2 * height) > TOP_DOWN_MAX_CELLS {
+            return self.sideways_lines();
+        }
+
+        let center = |index: usize| {
+            starts[index] + labels[index].len().saturating_sub(1) / 2
+        };
+        let index_of: HashMap<_, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (node_rc, _))| (Rc::as_ptr(node_rc), index))
+            .collect();
+        let mut levels = vec![Vec::new(); height];
+        for (index, (_, depth)) in nodes.iter().enumerate() {
+            levels[*depth].push(index);
+        }
+
+        let mut lines = Vec::with_capacity(2 * height);
+        for level in levels {
+            let mut key_line = vec![' '; line_width];
+            let mut connector_line = vec![' '; line_width];
+            for index in level {
+                for (offset, &cell) in labels[index].iter().enumerate() {
+                    put_cell(&mut key_line, starts[index] + offset, cell);
+                }
+
+                let node = nodes[index].0.borrow();
+                let child_center = |child: &Option<NodeRc<K, A>>| {
+                    child.as_ref().map(|child_rc| {
+                        center(index_of[&Rc::as_ptr(child_rc)])
+                    })
+                };
+                let (left_center, right_center) = (
+                    child_center(&node.left_child),
+                    child_center(&node.right_child),
+                );
+                if left_center.is_none() && right_center.is_none() {
+                    continue;
+                }
+
+                let node_center = center(index);
+                let from = left_center.unwrap_or(node_center);
+                let to = right_center.unwrap_or(node_center);
+                for column in from..=to.min(line_width.saturating_sub(1)) {
+                    put_cell(&mut connector_line, column, '─');
+                }
+                if let Some(left_center) = left_center {
+                    put_cell(&mut connector_line, left_center, '┌');
+                }
+                if let Some(right_center) = right_center {
+                    put_cell(&mut connector_line, right_center, '┐');
+                }
+                let joint = match (left_center, right_center) {
+                    (Some(_), Some(_)) => '┴',
+                    (Some(_), None) => '┘',
+                    _ => '└',
+                };
+                put_cell(&mut connector_line, node_center, joint);
+            }
+
+            let to_line = |line: Vec<char>| {
+                line.into_iter().collect::<String>().trim_end().to_string()
+            };
+            // the key line stays even if all its labels are empty, so every level keeps
+            // its row
+            lines.push(to_line(key_line));
+            let connector_line = to_line(connector_line);
+            if !connector_line.is_empty() {
+                lines.push(connector_line);
+            }
+        }
+
+        lines
+    }
+}
+
+// writes that fall after the cut are dropped
+fn put_cell(
+    line: &mut [char],
+    column: usize,
+    cell: char,
+) {
+    if let Some(slot) = line.get_mut(column) {
+        *slot = cell;
+    }
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> fmt::Display
+    for TreeRender<'_, K, A, C>
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if self.tree.root.is_none() {
+            return write!(f, "(empty)");
+        }
+
+        let lines = match self.layout {
+            RenderLayout::Sideways => self.sideways_lines(),
+            RenderLayout::TopDown => self.top_down_lines(),
+        };
+
+        for (line_index, line) in lines.iter().enumerate() {
+            if line_index > 0 {
+                writeln!(f)?;
+            }
+
+            match self.max_width {
+                Some(max_width) if line.chars().count() > max_width => {
+                    let cut: String = line
+                        .chars()
+                        .take(max_width.saturating_sub(1))
+                        .collect();
+                    write!(f, "{}…", cut)?;
+                }
+                _ => write!(f, "{}", line)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K: fmt::Debug, A: Augmentation<K>, C> fmt::Display
+    for BinarySearchTree<K, A, C>
+{
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::super::test_utils::sample_tree;
+    use super::*;
+
+    #[test]
+    fn sideways_drawing() {
+        let expected = [
+            "    ┌── 80",
+            "┌── 70",
+            "│   │   ┌── 65",
+            "│   └── 60",
+            "50",
+            "│   ┌── 40",
+            "└── 30",
+            "    └── 20",
+        ]
+        .join("\n");
+        assert_eq!(sample_tree().to_string(), expected);
+
+        let compact = ["┌ 3", "2", "└ 1"].join("\n");
+        let small = BinarySearchTree::from_sorted([1, 2, 3]);
+        assert_eq!(small.render().compact(true).to_string(), compact);
+    }
+
+    #[test]
+    fn top_down_drawing() {
+        let expected = [
+            "            50",
+            "    ┌───────┴───────────┐",
+            "    30                  70",
+            "┌───┴───┐       ┌───────┴───┐",
+            "20      40      60          80",
+            "                └───┐",
+            "                    65",
+        ]
+        .join("\n");
+        let bstree = sample_tree();
+        let drawing = bstree.render().layout(RenderLayout::TopDown);
+        assert_eq!(drawing.to_string(), expected, "\n{}", drawing);
+
+        let compact =
+            ["    \"b\"", " ┌───┴───┐", "\"a\"     \"c\""].join("\n");
+        let words: BinarySearchTree<&str> =
+            ["b", "a", "c"].into_iter().collect();
+        assert_eq!(
+            words
+                .render()
+                .layout(RenderLayout::TopDown)
+                .compact(true)
+                .to_string(),
+            compact
+        );
+    }
+
+    #[test]
+    fn width_limit_and_empty_tree() {
+        let cut = sample_tree()
+            .render()
+            .layout(RenderLayout::TopDown)
+            .max_width(10)
+            .to_string();
+        assert!(cut.lines().all(|line| line.chars().count() <= 10));
+        assert!(cut.lines().any(|line| line.ends_with('…')));
+
+        assert_eq!(BinarySearchTree::<i32>::new().to_string(), "(empty)");
+    }
+
+    #[test]
+    fn big_trees_stay_bounded() {
+        // 3000 levels and 3000 columns: 18M cells, drawn sideways
+        let mut degenerate = BinarySearchTree::new();
+        for value in 0..3000 {
+            degenerate.insert(value);
+        }
+        assert_eq!(
+            degenerate
+                .render()
+                .layout(RenderLayout::TopDown)
+                .to_string(),
+            degenerate.to_string()
+        );
+
+        // cut at 40 characters it fits, the lines never get wider than that
+        let cut = degenerate
+            .render()
+            .layout(RenderLayout::TopDown)
+            .max_width(40)
+            .to_string();
+        assert!(cut.starts_with("0\n└──┐\n   1"), "{}", cut);
+        assert!(cut.lines().all(|line| line.chars().count() <= 40));
+    }
+
+    #[test]
+    fn empty_labels() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Blank(i32);
+
+        impl fmt::Debug for Blank {
+            fn fmt(
+                &self,
+                _: &mut fmt::Formatter<'_>,
+            ) -> fmt::Result {
+                Ok(())
+            }
+        }
+
+        let mut bstree = BinarySearchTree::new();
+        for value in [2, 1, 3] {
+            bstree.insert(Blank(value));
+        }
+        // both key lines are blank, but they are still there
+        let drawing =
+            bstree.render().layout(RenderLayout::TopDown).to_string();
+        assert_eq!(drawing, ["", "┌─┴─┐", ""].join("\n"));
+    }
+}
@@ -0,0 +1,618 @@
+/*
+shape-preserving serialization: the tree that is read back has the same nodes in the same
+positions, not only the same keys (from_sorted / FromIterator would give a balanced tree).
+    * preorder with null markers: "5,3,#,4,#,#,8,#,#". Every node is followed by its left and
+      right subtree, a missing child is written as #. The empty tree is "#".
+    * level order, the LeetCode notation: "[5,3,8,null,4]". Nodes level by level, a missing
+      child is null, the nulls at the end are left out. The empty tree is "[]".
+    * binary: b"BST1", len as a varint (7 bits per byte, low bits first), then 2 bits per node
+      in preorder (bit 0: has a left child, bit 1: has a right child, 4 nodes per byte), then
+      the keys in preorder (BinaryKey: little endian for integers, varint length + utf-8 for
+      strings).
+text keys are written with Display and read with FromStr, so keys whose text has a comma (or
+is # / null) can not be read back.
+READING: the input gives the shape, the keys are only checked after the tree is built:
+    * every node is put into the next open child slot. Preorder and binary fill the slots
+      like a stack (the left subtree before the right one), level order like a queue.
+    * sizes and summaries are computed bottom-up, then validate runs. Keys out of order (or
+      duplicates) are rejected with the ValidationError, nothing is reordered or rebalanced.
+    * other errors: a token that is not a key, input that ends while slots are still open,
+      input left over when every slot is filled, missing [ ] or a wrong binary header.
+everything is iterative, a degenerate tree of any size can be written and read.
+*/
+
+use std::{
+    cell::RefCell, collections::VecDeque, error::Error, fmt, rc::Rc,
+    str::FromStr,
+};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc, validate::ValidationError,
+};
+
+const NULL_MARKER: &str = "#";
+const LEVEL_ORDER_NULL: &str = "null";
+const MAGIC: &[u8; 4] = b"BST1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError<K> {
+    // index of the token in the input (from 0), the token is neither a key nor a null marker
+    InvalidToken { index: usize, token: String },
+    // the input ended while some nodes still wait for a child
+    UnexpectedEnd,
+    // every node has its childs but there is more input, index of the first extra token
+    // (nodes for the binary format)
+    TrailingInput { index: usize },
+    MissingBrackets,
+    BadHeader,
+    // no key can be read from the bytes at offset
+    InvalidBytes { offset: usize },
+    // the shape is fine, but the keys do not make a binary search tree
+    Invalid(ValidationError<K>),
+}
+
+impl<K: fmt::Debug> fmt::Display for DeserializeError<K> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            DeserializeError::InvalidToken { index, token } => {
+                write!(f, "token {} ({:?}) is not a key", index, token)
+            }
+            DeserializeError::UnexpectedEnd => {
+                write!(f, "input ended before the tree was complete")
+            }
+            DeserializeError::TrailingInput { index } => {
+                write!(f, "input goes on after the tree, from {}", index)
+            }
+            DeserializeError::MissingBrackets => {
+                write!(f, "level order input must be in [ ]")
+            }
+            DeserializeError::BadHeader => {
+                write!(f, "binary input does not start with {:?}", MAGIC)
+            }
+            DeserializeError::InvalidBytes { offset } => {
+                write!(f, "no key can be read at byte {}", offset)
+            }
+            DeserializeError::Invalid(error) => {
+                write!(f, "not a binary search tree: {}", error)
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for DeserializeError<K> {}
+
+// keys of the binary format
+pub trait BinaryKey: Sized {
+    fn write_bytes(
+        &self,
+        out: &mut Vec<u8>,
+    );
+
+    // reads one key from the front of bytes and moves bytes past it
+    fn read_bytes(bytes: &mut &[u8]) -> Option<Self>;
+}
+
+macro_rules! binary_key_for_ints {
+    ($($int:ty),*) => {
+        $(
+            impl BinaryKey for $int {
+                fn write_bytes(
+                    &self,
+                    out: &mut Vec<u8>,
+                ) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_bytes(bytes: &mut &[u8]) -> Option<Self> {
+                    let (head, rest) = bytes
+                        .split_first_chunk::<{ std::mem::size_of::<$int>() }>()?;
+                    *bytes = rest;
+                    Some(<$int>::from_le_bytes(*head))
+                }
+            }
+        )*
+    };
+}
+
+binary_key_for_ints!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+impl BinaryKey for String {
+    fn write_bytes(
+        &self,
+        out: &mut Vec<u8>,
+    ) {
+        write_varint(self.len() as u64, out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_bytes(bytes: &mut &[u8]) -> Option<Self> {
+        let len = usize::try_from(read_varint(bytes)?).ok()?;
+        let (head, rest) = bytes.split_at_checked(len)?;
+        let key = String::from_utf8(head.to_vec()).ok()?;
+        *bytes = rest;
+        Some(key)
+    }
+}
+
+fn write_varint(
+    mut value: u64,
+    out: &mut Vec<u8>,
+) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7F).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+// where the next node goes: None for the root, else the parent and true for its left side
+type Slot<K, A> = Option<(NodeRc<K, A>, bool)>;
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    fn preorder_nodes(&self) -> Vec<NodeRc<K, A>> {
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut stack: Vec<NodeRc<K, A>> =
+            self.root.clone().into_iter().collect();
+
+        while let Some(node_rc) = stack.pop() {
+            let node = node_rc.borrow();
+            stack.extend(node.right_child.clone());
+            stack.extend(node.left_child.clone());
+            drop(node);
+            nodes.push(node_rc);
+        }
+
+        nodes
+    }
+
+    // a new node with key, linked into slot
+    fn fill_slot(
+        &mut self,
+        slot: Slot<K, A>,
+        key: K,
+    ) -> NodeRc<K, A> {
+        let node_rc = Rc::new(RefCell::new(BinarySearchTreeNode::new(key)));
+        match slot {
+            Some((parent_rc, is_left)) => {
+                node_rc.borrow_mut().parent =
+                    Some(Rc::downgrade(&parent_rc));
+                let mut parent = parent_rc.borrow_mut();
+                if is_left {
+                    parent.left_child = Some(node_rc.clone());
+                } else {
+                    parent.right_child = Some(node_rc.clone());
+                }
+            }
+            None => self.root = Some(node_rc.clone()),
+        }
+        self.len += 1;
+
+        node_rc
+    }
+}
+
+impl<K: fmt::Display, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn to_preorder_string(&self) -> String {
+        let mut tokens = Vec::with_capacity(2 * self.len + 1);
+        let mut stack = vec![self.root.clone()];
+
+        while let Some(node_ptr) = stack.pop() {
+            match node_ptr {
+                Some(node_rc) => {
+                    let node = node_rc.borrow();
+                    tokens.push(node.data.to_string());
+                    stack.push(node.right_child.clone());
+                    stack.push(node.left_child.clone());
+                }
+                None => tokens.push(NULL_MARKER.to_string()),
+            }
+        }
+
+        tokens.join(",")
+    }
+
+    pub fn to_level_order_string(&self) -> String {
+        let mut tokens = Vec::with_capacity(2 * self.len + 1);
+        let mut queue = VecDeque::from([self.root.clone()]);
+
+        while let Some(node_ptr) = queue.pop_front() {
+            match node_ptr {
+                Some(node_rc) => {
+                    let node = node_rc.borrow();
+                    tokens.push(node.data.to_string());
+                    queue.push_back(node.left_child.clone());
+                    queue.push_back(node.right_child.clone());
+                }
+                None => tokens.push(LEVEL_ORDER_NULL.to_string()),
+            }
+        }
+
+        while tokens.last().is_some_and(|token| token == LEVEL_ORDER_NULL) {
+            tokens.pop();
+        }
+
+        format!("[{}]", tokens.join(","))
+    }
+}
+
+impl<K: BinaryKey, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        write_varint(self.len as u64, &mut bytes);
+
+        let mut childs = vec![0u8; (2 * self.len).div_ceil(8)];
+        let mut keys = Vec::new();
+        for (index, node_rc) in self.preorder_nodes().iter().enumerate() {
+            let node = node_rc.borrow();
+            let flags = u8::from(node.left_child.is_some())
+                | (u8::from(node.right_child.is_some()) << 1);
+            childs[index / 4] |= flags << (index % 4 * 2);
+            node.data.write_bytes(&mut keys);
+        }
+
+        bytes.extend(childs);
+        bytes.extend(keys);
+        bytes
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Default>
+    BinarySearchTree<K, A, C>
+{
+    // sizes and summaries of the nodes that were linked by fill_slot, then the keys are
+    // checked
+    fn finish_reading(self) -> Result<Self, DeserializeError<K>> {
+        // childs come after their parent in preorder
+        for node_rc in self.preorder_nodes().iter().rev() {
+            let mut node = node_rc.borrow_mut();
+            node.size =
+                1 + BinarySearchTreeNode::subtree_size(
+                    node.left_child.as_ref(),
+                ) + BinarySearchTreeNode::subtree_size(
+                    node.right_child.as_ref(),
+                );
+            node.summary = BinarySearchTreeNode::summary_with_childs(&node);
+        }
+
+        self.validate().map_err(DeserializeError::Invalid)?;
+        Ok(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError<K>>
+    where
+        K: BinaryKey,
+    {
+        let mut rest = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(DeserializeError::BadHeader)?;
+        let len = read_varint(&mut rest)
+            .and_then(|len| usize::try_from(len).ok())
+            .ok_or(DeserializeError::UnexpectedEnd)?;
+        let (childs, mut keys) = len
+            .checked_mul(2)
+            .and_then(|bits| rest.split_at_checked(bits.div_ceil(8)))
+            .ok_or(DeserializeError::UnexpectedEnd)?;
+
+        let mut bstree = Self::default();
+        let mut slots: Vec<Slot<K, A>> =
+            if len > 0 { vec![None] } else { vec![] };
+        for index in 0..len {
+            let slot = slots
+                .pop()
+                .ok_or(DeserializeError::TrailingInput { index })?;
+            let offset = bytes.len() - keys.len();
+            let key = K::read_bytes(&mut keys)
+                .ok_or(DeserializeError::InvalidBytes { offset })?;
+            let node_rc = bstree.fill_slot(slot, key);
+
+            let flags = childs[index / 4] >> (index % 4 * 2);
+            if flags & 0b10 != 0 {
+                slots.push(Some((node_rc.clone(), false)));
+            }
+            if flags & 0b01 != 0 {
+                slots.push(Some((node_rc, true)));
+            }
+        }
+
+        if !slots.is_empty() {
+            return Err(DeserializeError::UnexpectedEnd);
+        }
+        if !keys.is_empty() {
+            return Err(DeserializeError::InvalidBytes {
+                offset: bytes.len() - keys.len(),
+            });
+        }
+
+        bstree.finish_reading()
+    }
+}
+
+impl<K: Clone + FromStr, A: Augmentation<K>, C: Comparator<K> + Default>
+    BinarySearchTree<K, A, C>
+{
+    pub fn from_preorder_str(
+        input: &str
+    ) -> Result<Self, DeserializeError<K>> {
+        let mut bstree = Self::default();
+        // the left slot is pushed last, the left subtree comes first
+        let mut slots: Vec<Slot<K, A>> = vec![None];
+
+        for (index, token) in input.split(',').map(str::trim).enumerate() {
+            let slot = slots
+                .pop()
+                .ok_or(DeserializeError::TrailingInput { index })?;
+            if token == NULL_MARKER {
+                continue;
+            }
+
+            let key = Self::parse_key(index, token)?;
+            let node_rc = bstree.fill_slot(slot, key);
+            slots.push(Some((node_rc.clone(), false)));
+            slots.push(Some((node_rc, true)));
+        }
+
+        if !slots.is_empty() {
+            return Err(DeserializeError::UnexpectedEnd);
+        }
+
+        bstree.finish_reading()
+    }
+
+    pub fn from_level_order_str(
+        input: &str
+    ) -> Result<Self, DeserializeError<K>> {
+        let inner = input
+            .trim()
+            .strip_prefix('[')
+            .and_then(|input| input.strip_suffix(']'))
+            .ok_or(DeserializeError::MissingBrackets)?
+            .trim();
+
+        let mut bstree = Self::default();
+        if inner.is_empty() {
+            return Ok(bstree);
+        }
+
+        // the trailing nulls are left out, so the input may end with open slots
+        let mut slots: VecDeque<Slot<K, A>> = VecDeque::from([None]);
+        for (index, token) in inner.split(',').map(str::trim).enumerate() {
+            let slot = slots
+                .pop_front()
+                .ok_or(DeserializeError::TrailingInput { index })?;
+            if token == LEVEL_ORDER_NULL {
+                continue;
+            }
+
+            let key = Self::parse_key(index, token)?;
+            let node_rc = bstree.fill_slot(slot, key);
+            slots.push_back(Some((node_rc.clone(), true)));
+            slots.push_back(Some((node_rc, false)));
+        }
+
+        bstree.finish_reading()
+    }
+
+    fn parse_key(
+        index: usize,
+        token: &str,
+    ) -> Result<K, DeserializeError<K>> {
+        token.parse().map_err(|_| DeserializeError::InvalidToken {
+            index,
+            token: token.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod serialize_tests {
+    use super::super::test_utils::tree_of;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn known_shapes() {
+        //     5
+        //   3   8
+        //    4
+        let bstree = tree_of(&[5, 3, 8, 4]);
+        assert_eq!(bstree.to_preorder_string(), "5,3,#,4,#,#,8,#,#");
+        assert_eq!(bstree.to_level_order_string(), "[5,3,8,null,4]");
+
+        let read = BinarySearchTree::<i32>::from_level_order_str(
+            "[5, 3, 8, null, 4]",
+        );
+        assert!(read.unwrap().structurally_eq(&bstree));
+        let read =
+            BinarySearchTree::<i32>::from_preorder_str("5,3,#,4,#,#,8,#,#");
+        assert!(read.unwrap().structurally_eq(&bstree));
+
+        let empty = BinarySearchTree::<i32>::new();
+        assert_eq!(empty.to_preorder_string(), "#");
+        assert_eq!(empty.to_level_order_string(), "[]");
+        assert_eq!(
+            BinarySearchTree::<i32>::from_level_order_str("[]")
+                .unwrap()
+                .get_len(),
+            0
+        );
+        assert_eq!(
+            BinarySearchTree::<i32>::from_bytes(&empty.to_bytes()),
+            Ok(empty)
+        );
+    }
+
+    #[test]
+    fn round_trips_keep_the_shape() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-1000i32..1000, 0..150);
+
+        runner
+            .run(&strategy, |values| {
+                let bstree = tree_of(&values);
+
+                let from_preorder =
+                    BinarySearchTree::<i32>::from_preorder_str(
+                        &bstree.to_preorder_string(),
+                    )
+                    .unwrap();
+                let from_level_order =
+                    BinarySearchTree::<i32>::from_level_order_str(
+                        &bstree.to_level_order_string(),
+                    )
+                    .unwrap();
+                let from_bytes =
+                    BinarySearchTree::<i32>::from_bytes(&bstree.to_bytes())
+                        .unwrap();
+
+                for read in [from_preorder, from_level_order, from_bytes] {
+                    prop_assert!(read.structurally_eq(&bstree));
+                    prop_assert_eq!(read.validate(), Ok(()));
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn string_keys_and_degenerate_trees() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec("[a-zé]{0,6}", 0..60);
+
+        runner
+            .run(&strategy, |words| {
+                let mut bstree = BinarySearchTree::new();
+                for word in words {
+                    bstree.insert(word);
+                }
+
+                let read = BinarySearchTree::<String>::from_bytes(
+                    &bstree.to_bytes(),
+                );
+                prop_assert!(read.unwrap().structurally_eq(&bstree));
+
+                Ok(())
+            })
+            .unwrap();
+
+        // one node per level (inserting sorted keys would take O(n^2)): 0,#,1,#,2,... each
+        // key has only a right child
+        let preorder = (0..20_000)
+            .map(|key| format!("{},#,", key))
+            .chain(["#".to_string()])
+            .collect::<String>();
+        let degenerate =
+            BinarySearchTree::<i32>::from_preorder_str(&preorder).unwrap();
+        assert_eq!(degenerate.height(), 20_000);
+        assert_eq!(degenerate.to_preorder_string(), preorder);
+
+        // 4 nodes per byte of shape + 4 bytes per key
+        let bytes = degenerate.to_bytes();
+        assert_eq!(bytes.len(), 4 + 3 + 20_000 / 4 + 4 * 20_000);
+        let read = BinarySearchTree::<i32>::from_bytes(&bytes).unwrap();
+        assert!(read.structurally_eq(&degenerate));
+    }
+
+    #[test]
+    fn rejects_bad_text() {
+        use DeserializeError::*;
+        type Tree = BinarySearchTree<i32>;
+
+        // 8 is the left child of 5, 3 the right child
+        assert_eq!(
+            Tree::from_level_order_str("[5,8,3]"),
+            Err(Invalid(ValidationError::OrderViolation {
+                key: 3,
+                lower: Some(5),
+                upper: None,
+            }))
+        );
+        // 6 is in the left subtree of 5
+        assert!(matches!(
+            Tree::from_preorder_str("5,3,#,6,#,#,#"),
+            Err(Invalid(ValidationError::OrderViolation { key: 6, .. }))
+        ));
+        assert!(matches!(
+            Tree::from_level_order_str("[5,null,5]"),
+            Err(Invalid(_))
+        ));
+
+        assert_eq!(
+            Tree::from_level_order_str("[5,x]"),
+            Err(InvalidToken {
+                index: 1,
+                token: "x".to_string(),
+            })
+        );
+        assert_eq!(Tree::from_level_order_str("5,3"), Err(MissingBrackets));
+        assert_eq!(
+            Tree::from_level_order_str("[5,3,null,null,null,null]"),
+            Err(TrailingInput { index: 5 })
+        );
+        assert_eq!(Tree::from_preorder_str("5,#"), Err(UnexpectedEnd));
+        assert_eq!(
+            Tree::from_preorder_str(""),
+            Err(InvalidToken {
+                index: 0,
+                token: String::new(),
+            })
+        );
+        assert_eq!(
+            Tree::from_preorder_str("5,#,#,7"),
+            Err(TrailingInput { index: 3 })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_bytes() {
+        use DeserializeError::*;
+        type Tree = BinarySearchTree<i32>;
+
+        let bytes = tree_of(&[5, 3, 8]).to_bytes();
+        assert_eq!(Tree::from_bytes(&bytes[1..]), Err(BadHeader));
+        assert_eq!(Tree::from_bytes(&bytes[..4]), Err(UnexpectedEnd));
+        assert_eq!(
+            Tree::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(InvalidBytes { offset: 14 })
+        );
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(
+            Tree::from_bytes(&longer),
+            Err(InvalidBytes { offset: 18 })
+        );
+
+        // the root says it has no childs, the other two nodes are left over
+        let mut no_childs = bytes.clone();
+        no_childs[5] = 0;
+        assert_eq!(
+            Tree::from_bytes(&no_childs),
+            Err(TrailingInput { index: 1 })
+        );
+
+        // 3 and 8 swapped
+        let mut swapped = bytes.clone();
+        swapped[10..14].copy_from_slice(&8i32.to_le_bytes());
+        swapped[14..18].copy_from_slice(&3i32.to_le_bytes());
+        assert!(matches!(Tree::from_bytes(&swapped), Err(Invalid(_))));
+    }
+}
@@ -0,0 +1,499 @@
+/*
+set algebra on two trees, the same operations as std's BTreeSet.
+    * the lazy iterators walk both trees in order at the same time (like the merge step of
+      merge sort). At every step the smaller key of the two sides is taken, and we know if it
+      is only in the left tree, only in the right tree or in both:
+        - union: every key, a key in both trees is given once
+        - intersection: keys in both
+        - difference: keys only in the left tree
+        - symmetric_difference: keys only in one of the trees
+      O(n + m) for the whole walk, keys come out sorted.
+    * union_tree, intersection_tree, difference_tree and symmetric_difference_tree build a
+      new tree from those iterators (&a | &b, &a & &b, &a - &b and &a ^ &b call them). The
+      keys are already sorted, so the new tree is built balanced in O(n + m) (from_sorted).
+      It gets the comparator of the left tree, the two trees must be ordered the same way.
+    * is_subset / is_superset / is_disjoint stop at the first key that decides the answer.
+*/
+
+use std::{
+    cmp::Ordering,
+    iter::Peekable,
+    ops::{BitAnd, BitOr, BitXor, Sub},
+};
+
+use super::{
+    Augmentation, BinarySearchTree, Comparator, NaturalOrder, iter::Iter,
+};
+
+// which tree(s) the key given by MergeIter is in
+enum Side {
+    Left,
+    Right,
+    Both,
+}
+
+// walks two trees in parallel, gives every key once together with its side
+struct MergeIter<'a, K: Clone, A: Augmentation<K>, C> {
+    left: Peekable<Iter<'a, K, A>>,
+    right: Peekable<Iter<'a, K, A>>,
+    // of the left tree, both trees must use the same order
+    comparator: &'a C,
+}
+
+pub struct Union<'a, K: Clone, A: Augmentation<K> = (), C = NaturalOrder> {
+    merged: MergeIter<'a, K, A, C>,
+}
+
+pub struct Intersection<
+    'a,
+    K: Clone,
+    A: Augmentation<K> = (),
+    C = NaturalOrder,
+> {
+    merged: MergeIter<'a, K, A, C>,
+}
+
+pub struct Difference<
+    'a,
+    K: Clone,
+    A: Augmentation<K> = (),
+    C = NaturalOrder,
+> {
+    merged: MergeIter<'a, K, A, C>,
+}
+
+pub struct SymmetricDifference<
+    'a,
+    K: Clone,
+    A: Augmentation<K> = (),
+    C = NaturalOrder,
+> {
+    merged: MergeIter<'a, K, A, C>,
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    MergeIter<'_, K, A, C>
+{
+    fn next(&mut self) -> Option<(K, Side)> {
+        let side = match (self.left.peek(), self.right.peek()) {
+            (Some(left_key), Some(right_key)) => {
+                match self.comparator.compare(left_key, right_key) {
+                    Ordering::Less => Side::Left,
+                    Ordering::Greater => Side::Right,
+                    Ordering::Equal => Side::Both,
+                }
+            }
+            (Some(_), None) => Side::Left,
+            (None, Some(_)) => Side::Right,
+            (None, None) => return None,
+        };
+
+        let key = match side {
+            Side::Left => self.left.next(),
+            Side::Right => self.right.next(),
+            Side::Both => {
+                self.right.next();
+                self.left.next()
+            }
+        };
+
+        key.map(|key| (key, side))
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    BinarySearchTree<K, A, C>
+{
+    fn merge_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> MergeIter<'a, K, A, C> {
+        MergeIter {
+            left: self.iter().peekable(),
+            right: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    pub fn union<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Union<'a, K, A, C> {
+        Union {
+            merged: self.merge_iter(other),
+        }
+    }
+
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Intersection<'a, K, A, C> {
+        Intersection {
+            merged: self.merge_iter(other),
+        }
+    }
+
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Difference<'a, K, A, C> {
+        Difference {
+            merged: self.merge_iter(other),
+        }
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifference<'a, K, A, C> {
+        SymmetricDifference {
+            merged: self.merge_iter(other),
+        }
+    }
+
+    // every key of self is in other
+    pub fn is_subset(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.len <= other.len && self.difference(other).next().is_none()
+    }
+
+    // every key of other is in self
+    pub fn is_superset(
+        &self,
+        other: &Self,
+    ) -> bool {
+        other.is_subset(self)
+    }
+
+    // no key is in both trees
+    pub fn is_disjoint(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for Union<'_, K, A, C>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.merged.next().map(|(key, _)| key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for Intersection<'_, K, A, C>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (key, Side::Both) = self.merged.next()? {
+                return Some(key);
+            }
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for Difference<'_, K, A, C>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (key, Side::Left) = self.merged.next()? {
+                return Some(key);
+            }
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for SymmetricDifference<'_, K, A, C>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.merged.next()? {
+                (_, Side::Both) => continue,
+                (key, _) => return Some(key),
+            }
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    BinarySearchTree<K, A, C>
+{
+    pub fn union_tree(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.tree_of_sorted(self.union(other))
+    }
+
+    pub fn intersection_tree(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.tree_of_sorted(self.intersection(other))
+    }
+
+    pub fn difference_tree(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.tree_of_sorted(self.difference(other))
+    }
+
+    pub fn symmetric_difference_tree(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.tree_of_sorted(self.symmetric_difference(other))
+    }
+
+    // keys come sorted out of the set iterators
+    fn tree_of_sorted(
+        &self,
+        keys: impl Iterator<Item = K>,
+    ) -> Self {
+        let mut bstree = self.empty_like();
+        bstree.fill_from_sorted(keys);
+        bstree
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    BitOr<&BinarySearchTree<K, A, C>> for &BinarySearchTree<K, A, C>
+{
+    type Output = BinarySearchTree<K, A, C>;
+
+    fn bitor(
+        self,
+        rhs: &BinarySearchTree<K, A, C>,
+    ) -> Self::Output {
+        self.union_tree(rhs)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    BitAnd<&BinarySearchTree<K, A, C>> for &BinarySearchTree<K, A, C>
+{
+    type Output = BinarySearchTree<K, A, C>;
+
+    fn bitand(
+        self,
+        rhs: &BinarySearchTree<K, A, C>,
+    ) -> Self::Output {
+        self.intersection_tree(rhs)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    Sub<&BinarySearchTree<K, A, C>> for &BinarySearchTree<K, A, C>
+{
+    type Output = BinarySearchTree<K, A, C>;
+
+    fn sub(
+        self,
+        rhs: &BinarySearchTree<K, A, C>,
+    ) -> Self::Output {
+        self.difference_tree(rhs)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    BitXor<&BinarySearchTree<K, A, C>> for &BinarySearchTree<K, A, C>
+{
+    type Output = BinarySearchTree<K, A, C>;
+
+    fn bitxor(
+        self,
+        rhs: &BinarySearchTree<K, A, C>,
+    ) -> Self::Output {
+        self.symmetric_difference_tree(rhs)
+    }
+}
+
+#[cfg(test)]
+mod set_ops_tests {
+    use std::collections::BTreeSet;
+
+    use super::super::test_utils::tree_of;
+    use super::*;
+    use proptest::prelude::*;
+
+    fn keys_of(bstree: &BinarySearchTree<i32>) -> Vec<i32> {
+        bstree.iter().collect()
+    }
+
+    fn sorted<'a>(keys: impl Iterator<Item = &'a i32>) -> Vec<i32> {
+        keys.copied().collect()
+    }
+
+    #[test]
+    fn set_operations_match_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-50i32..50, 0..100),
+            proptest::collection::vec(-50i32..50, 0..100),
+        );
+
+        runner
+            .run(&strategy, |(values, other_values)| {
+                let left: BinarySearchTree<i32> =
+                    values.iter().copied().collect();
+                let right: BinarySearchTree<i32> =
+                    other_values.iter().copied().collect();
+                let expected_left: BTreeSet<i32> =
+                    values.into_iter().collect();
+                let expected_right: BTreeSet<i32> =
+                    other_values.into_iter().collect();
+
+                let union = sorted(expected_left.union(&expected_right));
+                let intersection =
+                    sorted(expected_left.intersection(&expected_right));
+                let difference =
+                    sorted(expected_left.difference(&expected_right));
+                let symmetric_difference = sorted(
+                    expected_left.symmetric_difference(&expected_right),
+                );
+
+                prop_assert_eq!(
+                    left.union(&right).collect::<Vec<_>>(),
+                    union.clone()
+                );
+                prop_assert_eq!(
+                    left.intersection(&right).collect::<Vec<_>>(),
+                    intersection.clone()
+                );
+                prop_assert_eq!(
+                    left.difference(&right).collect::<Vec<_>>(),
+                    difference.clone()
+                );
+                prop_assert_eq!(
+                    left.symmetric_difference(&right).collect::<Vec<_>>(),
+                    symmetric_difference.clone()
+                );
+
+                prop_assert_eq!(keys_of(&(&left | &right)), union);
+                prop_assert_eq!(keys_of(&(&left & &right)), intersection);
+                prop_assert_eq!(keys_of(&(&left - &right)), difference);
+                prop_assert_eq!(
+                    keys_of(&(&left ^ &right)),
+                    symmetric_difference
+                );
+
+                prop_assert_eq!(
+                    left.is_subset(&right),
+                    expected_left.is_subset(&expected_right)
+                );
+                prop_assert_eq!(
+                    left.is_superset(&right),
+                    expected_left.is_superset(&expected_right)
+                );
+                prop_assert_eq!(
+                    left.is_disjoint(&right),
+                    expected_left.is_disjoint(&expected_right)
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn tree_methods_match_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-50i32..50, 0..100),
+            proptest::collection::vec(-50i32..50, 0..100),
+        );
+
+        runner
+            .run(&strategy, |(values, other_values)| {
+                let left = tree_of(&values);
+                let right = tree_of(&other_values);
+                let expected_left: BTreeSet<i32> =
+                    values.into_iter().collect();
+                let expected_right: BTreeSet<i32> =
+                    other_values.into_iter().collect();
+
+                let results = [
+                    (
+                        left.union_tree(&right),
+                        sorted(expected_left.union(&expected_right)),
+                    ),
+                    (
+                        left.intersection_tree(&right),
+                        sorted(expected_left.intersection(&expected_right)),
+                    ),
+                    (
+                        left.difference_tree(&right),
+                        sorted(expected_left.difference(&expected_right)),
+                    ),
+                    (
+                        left.symmetric_difference_tree(&right),
+                        sorted(
+                            expected_left
+                                .symmetric_difference(&expected_right),
+                        ),
+                    ),
+                ];
+                for (bstree, expected) in results {
+                    prop_assert_eq!(bstree.validate(), Ok(()));
+                    prop_assert_eq!(bstree.get_len(), expected.len());
+                    prop_assert_eq!(keys_of(&bstree), expected);
+                }
+
+                // the inputs are left alone
+                prop_assert_eq!(
+                    keys_of(&left),
+                    sorted(expected_left.iter())
+                );
+                prop_assert_eq!(
+                    keys_of(&right),
+                    sorted(expected_right.iter())
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn subsets_and_lazy_walks() {
+        let small: BinarySearchTree<i32> = [20, 40].into_iter().collect();
+        let big: BinarySearchTree<i32> =
+            [50, 30, 70, 20, 40, 60].into_iter().collect();
+        let other: BinarySearchTree<i32> = [10, 90].into_iter().collect();
+
+        assert!(small.is_subset(&big));
+        assert!(big.is_superset(&small));
+        assert!(!big.is_subset(&small));
+        assert!(big.is_disjoint(&other));
+        assert!(BinarySearchTree::new().is_subset(&small));
+
+        assert_eq!(
+            big.union(&other).take(3).collect::<Vec<_>>(),
+            vec![10, 20, 30]
+        );
+        assert_eq!(
+            big.difference(&small).collect::<Vec<_>>(),
+            vec![30, 50, 60, 70]
+        );
+
+        // the new tree is built balanced
+        let union = &big | &other;
+        assert_eq!(union.get_len(), 8);
+        assert_eq!(union.median(), Some(40));
+    }
+}
@@ -0,0 +1,348 @@
+/*
+split and join: cutting a tree in two at a pivot key and gluing trees back together.
+    * split_off(k): the nodes on the search path of k are the only nodes whose childs change.
+      Walking down the path, a node smaller than k keeps its left subtree and goes to the left
+      tree, hanging on the right side of the previous left node. A node >= k keeps its right
+      subtree and goes to the right tree, hanging on the left side of the previous right node.
+      The subtree we did not keep is where the walk continues. O(height), then the sizes and
+      summaries are recomputed on both new paths (bottom up).
+    * join(left, right): every key of left must be smaller than every key of right. The max
+      node of left is unlinked (at most 1 child, cheap) and becomes the new root with the two
+      trees as its subtrees. O(height of left), the result is one level higher than the
+      higher tree.
+    * append(other): join where the trees can come in any order, other is left empty.
+    * merge(other): for trees with overlapping keys. Both trees are consumed in sorted order,
+      merged like in merge sort and built back with from_sorted (balanced). O(n + m)
+*/
+
+use std::{cmp::Ordering, mem, rc::Rc};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+impl<K, A: Augmentation<K>, C: Comparator<K>> BinarySearchTree<K, A, C> {
+    // moves every key >= key into the returned tree
+    pub fn split_off(
+        &mut self,
+        key: &K,
+    ) -> Self
+    where
+        C: Clone,
+    {
+        let (mut left_root, mut right_root) = (None, None);
+        // last node added to each tree, the next node of that tree hangs below it
+        let mut left_tail: Option<NodeRc<K, A>> = None;
+        let mut right_tail: Option<NodeRc<K, A>> = None;
+        let mut current_node_ptr = self.root.take();
+
+        while let Some(node_rc) = current_node_ptr {
+            let goes_left = self
+                .comparator
+                .compare(&node_rc.borrow().data, key)
+                .is_lt();
+            if goes_left {
+                let mut node = node_rc.borrow_mut();
+                current_node_ptr = node.right_child.take();
+                node.parent = left_tail.as_ref().map(Rc::downgrade);
+                drop(node);
+
+                match left_tail.as_ref() {
+                    Some(tail_rc) => {
+                        tail_rc.borrow_mut().right_child =
+                            Some(node_rc.clone())
+                    }
+                    None => left_root = Some(node_rc.clone()),
+                }
+                left_tail = Some(node_rc);
+            } else {
+                let mut node = node_rc.borrow_mut();
+                current_node_ptr = node.left_child.take();
+                node.parent = right_tail.as_ref().map(Rc::downgrade);
+                drop(node);
+
+                match right_tail.as_ref() {
+                    Some(tail_rc) => {
+                        tail_rc.borrow_mut().left_child =
+                            Some(node_rc.clone())
+                    }
+                    None => right_root = Some(node_rc.clone()),
+                }
+                right_tail = Some(node_rc);
+            }
+        }
+
+        BinarySearchTreeNode::update_upward(left_tail);
+        BinarySearchTreeNode::update_upward(right_tail);
+
+        let left_len =
+            BinarySearchTreeNode::subtree_size(left_root.as_ref());
+        let right_len = self.len - left_len;
+        self.root = left_root;
+        self.len = left_len;
+
+        // the other half orders and deletes the same way
+        let mut right = self.empty_like();
+        right.root = right_root;
+        right.len = right_len;
+
+        right
+    }
+
+    // concatenates two trees, every key of left must be smaller than every key of right
+    pub fn join(
+        mut left: Self,
+        mut right: Self,
+    ) -> Self {
+        assert!(
+            Self::comes_before(&left, &right),
+            "join needs every key of left to be smaller than the keys of right"
+        );
+
+        let Some(left_max_rc) =
+            left.root.as_ref().map(BinarySearchTreeNode::max_of_subtree)
+        else {
+            return right;
+        };
+        if right.root.is_none() {
+            return left;
+        }
+
+        // the max node has no right child, delete_node unlinks that node itself
+        let middle_rc = left.delete_node(&left_max_rc);
+        let len = left.len + right.len + 1;
+
+        let (left_root, right_root) = (left.root.take(), right.root.take());
+        (left.len, right.len) = (0, 0);
+
+        for child_rc in left_root.iter().chain(right_root.iter()) {
+            child_rc.borrow_mut().parent = Some(Rc::downgrade(&middle_rc));
+        }
+
+        let mut middle = middle_rc.borrow_mut();
+        middle.left_child = left_root;
+        middle.right_child = right_root;
+        drop(middle);
+        BinarySearchTreeNode::update_upward(Some(middle_rc.clone()));
+
+        // left keeps its comparator and deletion strategy
+        left.root = Some(middle_rc);
+        left.len = len;
+
+        left
+    }
+
+    // moves every key of other into self, the key ranges of the trees must not overlap
+    // (use merge for that). other is left empty.
+    pub fn append(
+        &mut self,
+        other: &mut Self,
+    ) where
+        C: Clone,
+    {
+        // checked before anything is moved out, a panic leaves both trees as they were
+        let other_first = Self::comes_before(other, self);
+        assert!(
+            other_first || Self::comes_before(self, other),
+            "append needs trees whose key ranges do not overlap (use merge)"
+        );
+
+        let deletion = self.deletion;
+        let comparator = self.comparator.clone();
+        let mine = mem::replace(self, self.empty_like());
+        let theirs = mem::replace(other, other.empty_like());
+
+        *self = if other_first {
+            Self::join(theirs, mine)
+        } else {
+            Self::join(mine, theirs)
+        };
+        (self.deletion, self.comparator) = (deletion, comparator);
+    }
+
+    // true if every key of left is smaller than every key of right (or one of them is empty)
+    fn comes_before(
+        left: &Self,
+        right: &Self,
+    ) -> bool {
+        match (left.root.as_ref(), right.root.as_ref()) {
+            (Some(left_root_rc), Some(right_root_rc)) => {
+                let left_max_rc =
+                    BinarySearchTreeNode::max_of_subtree(left_root_rc);
+                let right_min_rc =
+                    BinarySearchTreeNode::min_of_subtree(right_root_rc);
+
+                let left_max = left_max_rc.borrow();
+                let right_min = right_min_rc.borrow();
+                left.comparator.compare(&left_max.data, &right_min.data)
+                    == Ordering::Less
+            }
+            _ => true,
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K> + Clone>
+    BinarySearchTree<K, A, C>
+{
+    // moves every key of other into self, keys can overlap (a key in both trees is kept
+    // once). The result is balanced.
+    pub fn merge(
+        &mut self,
+        other: Self,
+    ) {
+        let mine = mem::replace(self, self.empty_like()).into_iter();
+        let theirs = other.into_iter();
+
+        let mut merged = Vec::with_capacity(mine.len() + theirs.len());
+        let (mut mine, mut theirs) = (mine.peekable(), theirs.peekable());
+        loop {
+            let take_mine = match (mine.peek(), theirs.peek()) {
+                (Some(my_key), Some(their_key)) => {
+                    self.comparator.compare(my_key, their_key)
+                        != Ordering::Greater
+                }
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+
+            merged.extend(if take_mine {
+                mine.next()
+            } else {
+                theirs.next()
+            });
+        }
+
+        // equal keys are next to each other, fill_from_sorted keeps one of them
+        self.fill_from_sorted(merged);
+    }
+}
+
+#[cfg(test)]
+mod split_tests {
+    use std::{
+        collections::BTreeSet,
+        panic::{self, AssertUnwindSafe},
+    };
+
+    use super::super::test_utils::tree_of;
+    use super::*;
+    use proptest::prelude::*;
+
+    // prop_assert so failures inside runner.run are shrunk, the plain tests unwrap it
+    fn check_tree_eq(
+        bstree: &BinarySearchTree<i32>,
+        expected: &BTreeSet<i32>,
+    ) -> Result<(), TestCaseError> {
+        prop_assert_eq!(bstree.validate(), Ok(()));
+        prop_assert_eq!(bstree.get_len(), expected.len());
+        prop_assert_eq!(
+            bstree.iter().collect::<Vec<_>>(),
+            expected.iter().copied().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn split_off_and_append_match_btreeset() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..200),
+            -110i32..110,
+        );
+
+        runner
+            .run(&strategy, |(values, pivot)| {
+                let mut bstree = tree_of(&values);
+                let mut expected: BTreeSet<i32> =
+                    values.iter().copied().collect();
+                let all_keys = expected.clone();
+
+                let mut upper = bstree.split_off(&pivot);
+                let expected_upper = expected.split_off(&pivot);
+                check_tree_eq(&bstree, &expected)?;
+                check_tree_eq(&upper, &expected_upper)?;
+
+                // the halves are still working trees
+                prop_assert_eq!(bstree.rank(&pivot), expected.len());
+                prop_assert_eq!(
+                    upper.select(0),
+                    expected_upper.first().copied()
+                );
+
+                // in both orders
+                if values.len() % 2 == 0 {
+                    bstree.append(&mut upper);
+                } else {
+                    upper.append(&mut bstree);
+                    bstree = upper;
+                    upper = BinarySearchTree::new();
+                }
+                check_tree_eq(&bstree, &all_keys)?;
+                check_tree_eq(&upper, &BTreeSet::new())?;
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn merge_matches_btreeset_union() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = (
+            proptest::collection::vec(-100i32..100, 0..150),
+            proptest::collection::vec(-100i32..100, 0..150),
+        );
+
+        runner
+            .run(&strategy, |(values, other_values)| {
+                let mut bstree = tree_of(&values);
+                bstree.merge(tree_of(&other_values));
+
+                let expected: BTreeSet<i32> =
+                    values.into_iter().chain(other_values).collect();
+                check_tree_eq(&bstree, &expected)?;
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn join_puts_the_left_max_on_top() {
+        let left = tree_of(&[20, 10, 30]);
+        let right = tree_of(&[50, 40, 60]);
+
+        let joined = BinarySearchTree::join(left, right);
+        assert_eq!(joined.get_root().unwrap().borrow().data, 30);
+        check_tree_eq(&joined, &BTreeSet::from([10, 20, 30, 40, 50, 60]))
+            .unwrap();
+
+        let empty = BinarySearchTree::new();
+        let joined = BinarySearchTree::join(empty, joined);
+        assert_eq!(joined.get_len(), 6);
+    }
+
+    #[test]
+    fn append_rejects_overlapping_trees() {
+        let mut bstree = tree_of(&[10, 30]);
+        let mut other = tree_of(&[20]);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            bstree.append(&mut other)
+        }));
+        let message = result.unwrap_err();
+        assert!(
+            message
+                .downcast_ref::<&str>()
+                .is_some_and(|message| message.starts_with("append needs"))
+        );
+
+        // nothing was moved out before the panic
+        check_tree_eq(&bstree, &BTreeSet::from([10, 30])).unwrap();
+        check_tree_eq(&other, &BTreeSet::from([20])).unwrap();
+    }
+}
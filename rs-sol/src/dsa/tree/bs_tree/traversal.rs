@@ -0,0 +1,617 @@
+/*
+traversal orders other than in-order, all as lazy iterators.
+    * pre-order / post-order: walk with the parent pointers like the in-order Iter, no stack
+        - pre-order next: left child, else right child, else climb up until we come up from a
+          left child whose parent has a right child, that right child is next
+        - post-order first: go down (left if possible, otherwise right) until a leaf
+        - post-order next: the parent, unless we come up from its left child and it has a right
+          child, then it is the first post-order node of that right subtree
+    * reverse in-order: the in-order Iter from the back
+    * level based orders keep a queue of the nodes of the next level (breadth first search)
+        - level order: keys one by one, level by level, left to right
+        - levels: one Vec per level
+        - zigzag: one Vec per level, every second level right to left
+        - right side view: last key of every level (what you see looking from the right)
+    * vertical order: one Vec per column, left to right. A node's column is its parent's column
+      -1 for a left child and +1 for a right child. Inside a column, keys are ordered by depth,
+      keys on the same depth by the comparator of the tree. Columns are only known after
+      visiting every node, so the first call to next walks the whole tree, the columns are
+      handed out lazily after that.
+*/
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    iter::Rev,
+    marker::PhantomData,
+};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    GroupedTraversalType, NaturalOrder, NodeRc, TraversalType, iter::Iter,
+};
+
+pub struct PreOrderIter<'a, K, A = ()> {
+    next: Option<NodeRc<K, A>>,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+pub struct PostOrderIter<'a, K, A = ()> {
+    next: Option<NodeRc<K, A>>,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+pub struct LevelOrderIter<'a, K, A = ()> {
+    queue: VecDeque<NodeRc<K, A>>,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+// yields the nodes of one level at a time, the other level based iterators are built on it
+pub(super) struct LevelNodes<'a, K, A = ()> {
+    current_level: Vec<NodeRc<K, A>>,
+    _tree: PhantomData<&'a BinarySearchTree<K, A>>,
+}
+
+pub struct LevelsIter<'a, K, A = ()> {
+    levels: LevelNodes<'a, K, A>,
+}
+
+pub struct ZigZagIter<'a, K, A = ()> {
+    levels: LevelNodes<'a, K, A>,
+    right_to_left: bool,
+}
+
+pub struct RightSideViewIter<'a, K, A = ()> {
+    levels: LevelNodes<'a, K, A>,
+}
+
+pub struct VerticalOrderIter<'a, K, A = (), C = NaturalOrder> {
+    root: Option<NodeRc<K, A>>,
+    columns: Option<std::collections::btree_map::IntoValues<i64, Vec<K>>>,
+    // breaks the ties between keys on the same depth
+    comparator: &'a C,
+}
+
+impl<K, A: Augmentation<K>> BinarySearchTreeNode<K, A> {
+    fn next_in_preorder(node_rc: &NodeRc<K, A>) -> Option<NodeRc<K, A>> {
+        let (left_rc, right_rc) = {
+            let node = node_rc.borrow();
+            (node.left_child.clone(), node.right_child.clone())
+        };
+
+        if left_rc.is_some() {
+            return left_rc;
+        }
+        if right_rc.is_some() {
+            return right_rc;
+        }
+
+        // leaf node, climb up
+        let mut child_rc = node_rc.clone();
+        let mut maybe_parent_rc = node_rc.borrow().parent_rc();
+
+        while let Some(parent_rc) = maybe_parent_rc {
+            {
+                let parent_node = parent_rc.borrow();
+                if parent_node.is_left_child(&child_rc)
+                    && parent_node.right_child.is_some()
+                {
+                    return parent_node.right_child.clone();
+                }
+            }
+
+            maybe_parent_rc = parent_rc.borrow().parent_rc();
+            child_rc = parent_rc;
+        }
+
+        None
+    }
+
+    fn first_in_postorder(node_rc: &NodeRc<K, A>) -> NodeRc<K, A> {
+        let mut current_rc = node_rc.clone();
+
+        loop {
+            let next_rc = {
+                let current_node = current_rc.borrow();
+                current_node
+                    .left_child
+                    .clone()
+                    .or_else(|| current_node.right_child.clone())
+            };
+
+            match next_rc {
+                Some(next_rc) => current_rc = next_rc,
+                None => return current_rc,
+            }
+        }
+    }
+
+    fn next_in_postorder(node_rc: &NodeRc<K, A>) -> Option<NodeRc<K, A>> {
+        let parent_rc = node_rc.borrow().parent_rc()?;
+
+        let right_sibling_rc = {
+            let parent_node = parent_rc.borrow();
+            if parent_node.is_left_child(node_rc) {
+                parent_node.right_child.clone()
+            } else {
+                None
+            }
+        };
+
+        match right_sibling_rc {
+            Some(right_sibling_rc) => {
+                Some(Self::first_in_postorder(&right_sibling_rc))
+            }
+            None => Some(parent_rc),
+        }
+    }
+}
+
+impl<K, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    pub fn preorder_iter(&self) -> PreOrderIter<'_, K, A> {
+        PreOrderIter {
+            next: self.root.clone(),
+            _tree: PhantomData,
+        }
+    }
+
+    pub fn postorder_iter(&self) -> PostOrderIter<'_, K, A> {
+        PostOrderIter {
+            next: self
+                .root
+                .as_ref()
+                .map(BinarySearchTreeNode::first_in_postorder),
+            _tree: PhantomData,
+        }
+    }
+
+    pub fn reverse_inorder_iter(&self) -> Rev<Iter<'_, K, A>>
+    where
+        K: Clone,
+    {
+        self.iter().rev()
+    }
+
+    pub fn level_order_iter(&self) -> LevelOrderIter<'_, K, A> {
+        LevelOrderIter {
+            queue: self.root.iter().cloned().collect(),
+            _tree: PhantomData,
+        }
+    }
+
+    pub(super) fn level_nodes(&self) -> LevelNodes<'_, K, A> {
+        LevelNodes {
+            current_level: self.root.iter().cloned().collect(),
+            _tree: PhantomData,
+        }
+    }
+
+    pub fn levels_iter(&self) -> LevelsIter<'_, K, A> {
+        LevelsIter {
+            levels: self.level_nodes(),
+        }
+    }
+
+    pub fn zigzag_iter(&self) -> ZigZagIter<'_, K, A> {
+        ZigZagIter {
+            levels: self.level_nodes(),
+            right_to_left: false,
+        }
+    }
+
+    pub fn right_side_view_iter(&self) -> RightSideViewIter<'_, K, A> {
+        RightSideViewIter {
+            levels: self.level_nodes(),
+        }
+    }
+
+    pub fn vertical_order_iter(&self) -> VerticalOrderIter<'_, K, A, C> {
+        VerticalOrderIter {
+            root: self.root.clone(),
+            columns: None,
+            comparator: &self.comparator,
+        }
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C> BinarySearchTree<K, A, C> {
+    // None for an empty tree, like it always was
+    pub fn traverse(
+        &self,
+        traversal_type: TraversalType,
+    ) -> Option<Vec<K>> {
+        self.root.as_ref()?;
+
+        let keys = match traversal_type {
+            TraversalType::InOrder => self.iter().collect(),
+            TraversalType::PreOrder => self.preorder_iter().collect(),
+            TraversalType::PostOrder => self.postorder_iter().collect(),
+            TraversalType::ReverseInOrder => {
+                self.reverse_inorder_iter().collect()
+            }
+            TraversalType::LevelOrder => self.level_order_iter().collect(),
+            TraversalType::RightSideView => {
+                self.right_side_view_iter().collect()
+            }
+        };
+
+        Some(keys)
+    }
+
+    pub fn traverse_grouped(
+        &self,
+        traversal_type: GroupedTraversalType,
+    ) -> Option<Vec<Vec<K>>>
+    where
+        C: Comparator<K>,
+    {
+        self.root.as_ref()?;
+
+        let groups = match traversal_type {
+            GroupedTraversalType::Levels => self.levels_iter().collect(),
+            GroupedTraversalType::ZigZag => self.zigzag_iter().collect(),
+            GroupedTraversalType::Vertical => {
+                self.vertical_order_iter().collect()
+            }
+        };
+
+        Some(groups)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for PreOrderIter<'_, K, A> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_rc = self.next.take()?;
+        self.next = BinarySearchTreeNode::next_in_preorder(&current_rc);
+
+        let key = current_rc.borrow().data.clone();
+        Some(key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for PostOrderIter<'_, K, A> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_rc = self.next.take()?;
+        self.next = BinarySearchTreeNode::next_in_postorder(&current_rc);
+
+        let key = current_rc.borrow().data.clone();
+        Some(key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for LevelOrderIter<'_, K, A> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_rc = self.queue.pop_front()?;
+        let current_node = current_rc.borrow();
+
+        self.queue.extend(current_node.left_child.clone());
+        self.queue.extend(current_node.right_child.clone());
+
+        Some(current_node.data.clone())
+    }
+}
+
+impl<K, A: Augmentation<K>> Iterator for LevelNodes<'_, K, A> {
+    type Item = Vec<NodeRc<K, A>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_level.is_empty() {
+            return None;
+        }
+
+        let mut next_level = Vec::new();
+        for node_rc in &self.current_level {
+            let node = node_rc.borrow();
+            next_level.extend(node.left_child.clone());
+            next_level.extend(node.right_child.clone());
+        }
+
+        Some(std::mem::replace(&mut self.current_level, next_level))
+    }
+}
+
+fn keys_of<K: Clone, A>(level: &[NodeRc<K, A>]) -> Vec<K> {
+    level
+        .iter()
+        .map(|node_rc| node_rc.borrow().data.clone())
+        .collect()
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for LevelsIter<'_, K, A> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.levels.next().map(|level| keys_of(&level))
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator for ZigZagIter<'_, K, A> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut keys = keys_of(&self.levels.next()?);
+
+        if self.right_to_left {
+            keys.reverse();
+        }
+        self.right_to_left = !self.right_to_left;
+
+        Some(keys)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>> Iterator
+    for RightSideViewIter<'_, K, A>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // a level is never empty, LevelNodes stops before that
+        let level = self.levels.next()?;
+        let key = level.last()?.borrow().data.clone();
+        Some(key)
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    VerticalOrderIter<'_, K, A, C>
+{
+    fn collect_columns(
+        &self,
+        root_rc: NodeRc<K, A>,
+    ) -> BTreeMap<i64, Vec<K>> {
+        // column -> (depth, key) pairs
+        let mut columns: BTreeMap<i64, Vec<(usize, K)>> = BTreeMap::new();
+        let mut queue = VecDeque::from([(root_rc, 0i64, 0usize)]);
+
+        while let Some((node_rc, column, depth)) = queue.pop_front() {
+            let node = node_rc.borrow();
+            columns
+                .entry(column)
+                .or_default()
+                .push((depth, node.data.clone()));
+
+            if let Some(left_rc) = node.left_child.clone() {
+                queue.push_back((left_rc, column - 1, depth + 1));
+            }
+            if let Some(right_rc) = node.right_child.clone() {
+                queue.push_back((right_rc, column + 1, depth + 1));
+            }
+        }
+
+        columns
+            .into_iter()
+            .map(|(column, mut entries)| {
+                entries.sort_by(
+                    |(depth, key), (other_depth, other_key)| {
+                        depth.cmp(other_depth).then_with(|| {
+                            self.comparator.compare(key, other_key)
+                        })
+                    },
+                );
+                (column, entries.into_iter().map(|(_, key)| key).collect())
+            })
+            .collect()
+    }
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>> Iterator
+    for VerticalOrderIter<'_, K, A, C>
+{
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.columns.is_none() {
+            let root_rc = self.root.take()?;
+            self.columns =
+                Some(self.collect_columns(root_rc).into_values());
+        }
+
+        self.columns.as_mut()?.next()
+    }
+}
+
+#[cfg(test)]
+mod traversal_tests {
+    use super::super::{ReverseOrder, test_utils::sample_tree};
+    use super::*;
+    use proptest::prelude::*;
+
+    // recursive reference implementations, only used to check the iterators
+    fn preorder_recursive(
+        node: Option<&NodeRc<i32>>,
+        acc: &mut Vec<i32>,
+    ) {
+        if let Some(node_rc) = node {
+            let node = node_rc.borrow();
+            acc.push(node.data);
+            preorder_recursive(node.left_child.as_ref(), acc);
+            preorder_recursive(node.right_child.as_ref(), acc);
+        }
+    }
+
+    fn postorder_recursive(
+        node: Option<&NodeRc<i32>>,
+        acc: &mut Vec<i32>,
+    ) {
+        if let Some(node_rc) = node {
+            let node = node_rc.borrow();
+            postorder_recursive(node.left_child.as_ref(), acc);
+            postorder_recursive(node.right_child.as_ref(), acc);
+            acc.push(node.data);
+        }
+    }
+
+    #[test]
+    fn every_order_on_a_known_tree() {
+        let bstree = sample_tree();
+
+        let expected = [
+            (TraversalType::InOrder, vec![20, 30, 40, 50, 60, 65, 70, 80]),
+            (
+                TraversalType::PreOrder,
+                vec![50, 30, 20, 40, 70, 60, 65, 80],
+            ),
+            (
+                TraversalType::PostOrder,
+                vec![20, 40, 30, 65, 60, 80, 70, 50],
+            ),
+            (
+                TraversalType::ReverseInOrder,
+                vec![80, 70, 65, 60, 50, 40, 30, 20],
+            ),
+            (
+                TraversalType::LevelOrder,
+                vec![50, 30, 70, 20, 40, 60, 80, 65],
+            ),
+            (TraversalType::RightSideView, vec![50, 70, 80, 65]),
+        ];
+
+        for (traversal_type, keys) in expected {
+            assert_eq!(bstree.traverse(traversal_type), Some(keys));
+        }
+
+        assert_eq!(
+            bstree.traverse_grouped(GroupedTraversalType::Levels),
+            Some(vec![
+                vec![50],
+                vec![30, 70],
+                vec![20, 40, 60, 80],
+                vec![65]
+            ])
+        );
+        assert_eq!(
+            bstree.traverse_grouped(GroupedTraversalType::ZigZag),
+            Some(vec![
+                vec![50],
+                vec![70, 30],
+                vec![20, 40, 60, 80],
+                vec![65]
+            ])
+        );
+        // columns: 20(-2) 30(-1) 50,40,60(0) 70,65(1) 80(2)
+        assert_eq!(
+            bstree.traverse_grouped(GroupedTraversalType::Vertical),
+            Some(vec![
+                vec![20],
+                vec![30],
+                vec![50, 40, 60],
+                vec![70, 65],
+                vec![80]
+            ])
+        );
+    }
+
+    #[test]
+    fn vertical_order_uses_the_comparator() {
+        //        50
+        //     70    30
+        //       60 40
+        let mut reversed = BinarySearchTree::with_comparator(ReverseOrder);
+        for value in [50, 70, 30, 60, 40] {
+            reversed.insert(value);
+        }
+        // 60 and 40 are both on depth 2 of the middle column
+        assert_eq!(
+            reversed.traverse_grouped(GroupedTraversalType::Vertical),
+            Some(vec![vec![70], vec![50, 60, 40], vec![30]])
+        );
+
+        // f64 is not Ord, the comparator is enough
+        let mut floats =
+            BinarySearchTree::with_comparator(|a: &f64, b: &f64| {
+                a.total_cmp(b)
+            });
+        for value in [2.0, 1.0, 3.0, 1.5, 2.5] {
+            floats.insert(value);
+        }
+        assert_eq!(
+            floats.vertical_order_iter().collect::<Vec<_>>(),
+            [vec![1.0], vec![2.0, 1.5, 2.5], vec![3.0]]
+        );
+    }
+
+    #[test]
+    fn empty_tree_traversals() {
+        let bstree: BinarySearchTree<i32> = BinarySearchTree::new();
+
+        assert_eq!(bstree.traverse(TraversalType::LevelOrder), None);
+        assert_eq!(
+            bstree.traverse_grouped(GroupedTraversalType::Vertical),
+            None
+        );
+        assert_eq!(bstree.preorder_iter().next(), None);
+        assert_eq!(bstree.postorder_iter().next(), None);
+        assert_eq!(bstree.zigzag_iter().next(), None);
+        assert_eq!(bstree.vertical_order_iter().next(), None);
+    }
+
+    #[test]
+    fn orders_match_reference_implementations() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy = proptest::collection::vec(-300i32..300, 1..300);
+
+        runner
+            .run(&strategy, |values| {
+                let mut bstree = BinarySearchTree::new();
+                for &value in &values {
+                    bstree.insert(value);
+                }
+
+                let mut preorder = Vec::new();
+                preorder_recursive(bstree.get_root(), &mut preorder);
+                prop_assert_eq!(
+                    bstree.preorder_iter().collect::<Vec<_>>(),
+                    preorder
+                );
+
+                let mut postorder = Vec::new();
+                postorder_recursive(bstree.get_root(), &mut postorder);
+                prop_assert_eq!(
+                    bstree.postorder_iter().collect::<Vec<_>>(),
+                    postorder
+                );
+
+                let levels = bstree.levels_iter().collect::<Vec<_>>();
+                prop_assert_eq!(
+                    bstree.level_order_iter().collect::<Vec<_>>(),
+                    levels.concat()
+                );
+                prop_assert_eq!(
+                    bstree.right_side_view_iter().collect::<Vec<_>>(),
+                    levels
+                        .iter()
+                        .map(|level| *level.last().unwrap())
+                        .collect::<Vec<_>>()
+                );
+
+                for (depth, (zigzag, level)) in
+                    bstree.zigzag_iter().zip(&levels).enumerate()
+                {
+                    let mut expected = level.clone();
+                    if depth % 2 == 1 {
+                        expected.reverse();
+                    }
+                    prop_assert_eq!(zigzag, expected);
+                }
+
+                let mut vertical = bstree
+                    .vertical_order_iter()
+                    .flatten()
+                    .collect::<Vec<_>>();
+                vertical.sort();
+                prop_assert_eq!(
+                    vertical,
+                    bstree.iter().collect::<Vec<_>>()
+                );
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+/*
+structural invariants of the tree. The other tests check behaviour (search, get_len, the
+traversals), validate checks the links themselves:
+    * ordering: every key is strictly inside the bounds given by its ancestors. Checking only a
+      node against its childs is not enough, a key in the right subtree of a left child can
+      still be bigger than the grand parent. Strict, because there are no duplicates.
+    * parent pointers: the parent of every child upgrades to the node that holds the child,
+      and the root has no parent.
+    * len is the number of nodes that can be reached from the root.
+    * subtree sizes: size == 1 + size of left child + size of right child at every node. Since
+      a leaf must have size 1, this makes every size equal to the real node count below it.
+      Sizes are checked in reverse preorder (childs before their parent), so a wrong size is
+      reported at the node that has it and not at its parent.
+the walk uses an explicit stack (like the traversals), so a degenerate tree is fine too.
+*/
+
+use std::{cmp::Ordering, error::Error, fmt, rc::Rc};
+
+use super::{
+    Augmentation, BinarySearchTree, BinarySearchTreeNode, Comparator,
+    NodeRc,
+};
+
+// the first broken invariant found, keys are cloned out of the tree to identify the node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError<K> {
+    // key is not strictly between the keys of its ancestors (None: no bound on that side)
+    OrderViolation {
+        key: K,
+        lower: Option<K>,
+        upper: Option<K>,
+    },
+    RootHasParent {
+        root: K,
+    },
+    // the child of parent does not point back to parent (found: where it points to, None if
+    // it has no parent or the parent is already dropped)
+    WrongParent {
+        key: K,
+        parent: K,
+        found: Option<K>,
+    },
+    WrongSize {
+        key: K,
+        size: usize,
+        expected: usize,
+    },
+    // counting stops at len + 1, a cycle in the child links would never end otherwise
+    LenMismatch {
+        len: usize,
+        node_count: usize,
+    },
+}
+
+impl<K: fmt::Debug> fmt::Display for ValidationError<K> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            ValidationError::OrderViolation { key, lower, upper } => {
+                write!(
+                    f,
+                    "node {:?} is out of its bounds: lower {:?}, upper {:?}",
+                    key, lower, upper
+                )
+            }
+            ValidationError::RootHasParent { root } => {
+                write!(f, "root node {:?} has a parent", root)
+            }
+            ValidationError::WrongParent { key, parent, found } => write!(
+                f,
+                "node {:?} is a child of {:?} but its parent pointer gives {:?}",
+                key, parent, found
+            ),
+            ValidationError::WrongSize {
+                key,
+                size,
+                expected,
+            } => write!(
+                f,
+                "node {:?} has subtree size {} but its childs give {}",
+                key, size, expected
+            ),
+            ValidationError::LenMismatch { len, node_count } => write!(
+                f,
+                "len is {} but {} nodes are reachable from the root",
+                len, node_count
+            ),
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for ValidationError<K> {}
+
+// a node to check, together with what its ancestors expect from it
+struct Pending<K, A> {
+    node_rc: NodeRc<K, A>,
+    parent: Option<NodeRc<K, A>>,
+    // the closest ancestors the node must be bigger / smaller than
+    lower: Option<NodeRc<K, A>>,
+    upper: Option<NodeRc<K, A>>,
+}
+
+fn key_of<K: Clone, A>(node_rc: &NodeRc<K, A>) -> K {
+    node_rc.borrow().data.clone()
+}
+
+impl<K: Clone, A: Augmentation<K>, C: Comparator<K>>
+    BinarySearchTree<K, A, C>
+{
+    pub fn validate(&self) -> Result<(), ValidationError<K>> {
+        let mut stack: Vec<Pending<K, A>> = Vec::new();
+        if let Some(root_rc) = self.root.as_ref() {
+            if root_rc.borrow().parent_rc().is_some() {
+                return Err(ValidationError::RootHasParent {
+                    root: key_of(root_rc),
+                });
+            }
+
+            stack.push(Pending {
+                node_rc: root_rc.clone(),
+                parent: None,
+                lower: None,
+                upper: None,
+            });
+        }
+
+        let mut preorder: Vec<NodeRc<K, A>> = Vec::with_capacity(self.len);
+        while let Some(pending) = stack.pop() {
+            if preorder.len() == self.len {
+                // one more node than len
+                return Err(ValidationError::LenMismatch {
+                    len: self.len,
+                    node_count: self.len + 1,
+                });
+            }
+
+            self.check_links(&pending)?;
+            preorder.push(pending.node_rc.clone());
+
+            let node = pending.node_rc.borrow();
+            // the node is the upper bound of its left subtree and the lower bound of
+            // its right subtree
+            stack.extend(node.left_child.clone().map(|left_rc| Pending {
+                node_rc: left_rc,
+                parent: Some(pending.node_rc.clone()),
+                lower: pending.lower.clone(),
+                upper: Some(pending.node_rc.clone()),
+            }));
+            stack.extend(node.right_child.clone().map(|right_rc| {
+                Pending {
+                    node_rc: right_rc,
+                    parent: Some(pending.node_rc.clone()),
+                    lower: Some(pending.node_rc.clone()),
+                    upper: pending.upper.clone(),
+                }
+            }));
+        }
+
+        if preorder.len() != self.len {
+            return Err(ValidationError::LenMismatch {
+                len: self.len,
+                node_count: preorder.len(),
+            });
+        }
+
+        preorder.iter().rev().try_for_each(Self::check_size)
+    }
+
+    // ordering and parent pointer of one node
+    fn check_links(
+        &self,
+        pending: &Pending<K, A>,
+    ) -> Result<(), ValidationError<K>> {
+        let node = pending.node_rc.borrow();
+        let is_less = |left: &K, right: &K| {
+            self.comparator.compare(left, right) == Ordering::Less
+        };
+
+        let above_lower = pending.lower.as_ref().is_none_or(|lower_rc| {
+            is_less(&lower_rc.borrow().data, &node.data)
+        });
+        let below_upper = pending.upper.as_ref().is_none_or(|upper_rc| {
+            is_less(&node.data, &upper_rc.borrow().data)
+        });
+        if !above_lower || !below_upper {
+            return Err(ValidationError::OrderViolation {
+                key: node.data.clone(),
+                lower: pending.lower.as_ref().map(key_of),
+                upper: pending.upper.as_ref().map(key_of),
+            });
+        }
+
+        if let Some(parent_rc) = pending.parent.as_ref() {
+            let found_rc = node.parent_rc();
+            if !found_rc
+                .as_ref()
+                .is_some_and(|found_rc| Rc::ptr_eq(found_rc, parent_rc))
+            {
+                return Err(ValidationError::WrongParent {
+                    key: node.data.clone(),
+                    parent: key_of(parent_rc),
+                    found: found_rc.as_ref().map(key_of),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_size(
+        node_rc: &NodeRc<K, A>
+    ) -> Result<(), ValidationError<K>> {
+        let node = node_rc.borrow();
+        let expected = 1
+            + BinarySearchTreeNode::subtree_size(node.left_child.as_ref())
+            + BinarySearchTreeNode::subtree_size(node.right_child.as_ref());
+        if node.size != expected {
+            return Err(ValidationError::WrongSize {
+                key: node.data.clone(),
+                size: node.size,
+                expected,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::super::test_utils::sample_tree;
+    use super::*;
+    use proptest::prelude::*;
+
+    fn node(
+        bstree: &BinarySearchTree<i32>,
+        key: i32,
+    ) -> NodeRc<i32> {
+        bstree.search(&key).unwrap()
+    }
+
+    #[test]
+    fn every_operation_keeps_the_tree_valid() {
+        let mut runner = proptest::test_runner::TestRunner::default();
+        let strategy =
+            proptest::collection::vec((0u8..4, -50i32..50), 0..200);
+
+        runner
+            .run(&strategy, |operations| {
+                let mut bstree = BinarySearchTree::new();
+
+                for (operation, value) in operations {
+                    match operation {
+                        0 | 1 => bstree.insert(value),
+                        2 => bstree.delete(&value),
+                        _ => {
+                            let mut upper = bstree.split_off(&value);
+                            prop_assert_eq!(upper.validate(), Ok(()));
+                            upper.rebalance();
+                            bstree.append(&mut upper);
+                        }
+                    }
+                    prop_assert_eq!(bstree.validate(), Ok(()));
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn broken_ordering_is_reported_with_bounds() {
+        let bstree = sample_tree();
+        // 55 is fine next to its parent 30 (right child, bigger), but it is in the left
+        // subtree of the root 50
+        node(&bstree, 40).borrow_mut().data = 55;
+
+        assert_eq!(
+            bstree.validate(),
+            Err(ValidationError::OrderViolation {
+                key: 55,
+                lower: Some(30),
+                upper: Some(50),
+            })
+        );
+    }
+
+    #[test]
+    fn broken_links_and_counts_are_reported() {
+        let bstree = sample_tree();
+        let root_rc = node(&bstree, 50);
+        node(&bstree, 20).borrow_mut().parent =
+            Some(Rc::downgrade(&root_rc));
+        assert_eq!(
+            bstree.validate(),
+            Err(ValidationError::WrongParent {
+                key: 20,
+                parent: 30,
+                found: Some(50),
+            })
+        );
+
+        let mut bstree = sample_tree();
+        let root_rc = node(&bstree, 50);
+        root_rc.borrow_mut().parent =
+            Some(Rc::downgrade(&node(&bstree, 80)));
+        let error = bstree.validate().unwrap_err();
+        assert_eq!(error, ValidationError::RootHasParent { root: 50 });
+        assert_eq!(error.to_string(), "root node 50 has a parent");
+        root_rc.borrow_mut().parent = None;
+
+        node(&bstree, 70).borrow_mut().size = 2;
+        assert_eq!(
+            bstree.validate(),
+            Err(ValidationError::WrongSize {
+                key: 70,
+                size: 2,
+                expected: 4,
+            })
+        );
+        node(&bstree, 70).borrow_mut().size = 4;
+
+        bstree.len = 9;
+        assert_eq!(
+            bstree.validate(),
+            Err(ValidationError::LenMismatch {
+                len: 9,
+                node_count: 8,
+            })
+        );
+    }
+}